@@ -0,0 +1,7599 @@
+use std::marker::PhantomData;
+use std::ops::Add;
+use std::ops::Sub;
+use std::ops::Mul;
+use std::sync::atomic::{AtomicU64, Ordering};
+use rayon::prelude::*;
+
+extern crate nalgebra_glm as glm;
+
+// A raw pointer to the first byte of a buffer, shared across the rayon
+// scanline workers in `draw_flat_triangle_common`. Each worker only derives
+// an offset into its own disjoint row, so concurrent use is sound even
+// though the compiler can't see that through a raw pointer.
+#[derive(Clone, Copy)]
+struct RowPtr(*mut u8);
+unsafe impl Send for RowPtr {}
+unsafe impl Sync for RowPtr {}
+
+
+// Barycentric weights via incremental edge functions rather than recomputing
+// a dot-product solve from scratch at every pixel. Each edge function is
+// linear in `x` and `y`, so it can be evaluated once per row and then walked
+// forward with a constant per-pixel delta (`step_x`), which is both cheaper
+// and, unlike the dot-product form, doesn't divide by a near-zero Gram
+// determinant for thin triangles.
+#[derive(Clone, Copy)]
+struct BarycentricSetup {
+    inv_area: f32,
+    a0: f32, b0: f32, c0: f32,
+    a1: f32, b1: f32, c1: f32,
+    a2: f32, b2: f32, c2: f32
+}
+
+impl BarycentricSetup {
+    fn new(p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4) -> Self {
+        let edge_coeffs = |a: &glm::Vec4, b: &glm::Vec4| {
+            let da = b.y - a.y;
+            let db = a.x - b.x;
+            let dc = -(da * a.x + db * a.y);
+            (da, db, dc)
+        };
+        let (a0, b0, c0) = edge_coeffs(p1, p2);
+        let (a1, b1, c1) = edge_coeffs(p2, p0);
+        let (a2, b2, c2) = edge_coeffs(p0, p1);
+        let area = a2 * p2.x + b2 * p2.y + c2;
+        BarycentricSetup { inv_area: 1.0 / area, a0, b0, c0, a1, b1, c1, a2, b2, c2 }
+    }
+
+    fn edges_at(&self, x: f32, y: f32) -> (f32, f32, f32) {
+        (
+            self.a0 * x + self.b0 * y + self.c0,
+            self.a1 * x + self.b1 * y + self.c1,
+            self.a2 * x + self.b2 * y + self.c2
+        )
+    }
+
+    // `f0` is derived as `1 - f1 - f2` rather than its own `e0 * inv_area`,
+    // the same trick the old dot-product solve used, so the three weights
+    // always sum to exactly 1 even after many incremental steps have nudged
+    // `f1`/`f2` by their rounding error.
+    fn weights_at(&self, x: f32, y: f32) -> (f32, f32, f32) {
+        let (_, e1, e2) = self.edges_at(x, y);
+        let (f1, f2) = (e1 * self.inv_area, e2 * self.inv_area);
+        (1.0 - f1 - f2, f1, f2)
+    }
+
+    // Per-pixel delta when stepping one pixel to the right; constant across
+    // the whole triangle since each edge function is linear in `x`.
+    fn step_x(&self) -> (f32, f32) {
+        (self.a1 * self.inv_area, self.a2 * self.inv_area)
+    }
+
+    // Same weights as four consecutive calls to `weights_at(x0, y)`,
+    // `weights_at(x0 + 1, y)`, ..., but with the four lanes' worth of work
+    // laid out side-by-side (structure-of-arrays) instead of interleaved,
+    // so LLVM can fold each line into a single vector instruction. Bit-
+    // identical to the scalar path since it's the same linear formula per
+    // lane, just batched; gated behind `simd_barycentric` since it only
+    // pays off in the hot per-pixel loop.
+    #[cfg(feature = "simd_barycentric")]
+    fn edges_batch4(&self, x0: f32, y: f32) -> [(f32, f32, f32); 4] {
+        let xs = [x0, x0 + 1.0, x0 + 2.0, x0 + 3.0];
+        let mut e0 = [0.0f32; 4];
+        let mut e1 = [0.0f32; 4];
+        let mut e2 = [0.0f32; 4];
+        let by = self.b0 * y + self.c0;
+        let b1y = self.b1 * y + self.c1;
+        let b2y = self.b2 * y + self.c2;
+        for lane in 0..4 {
+            e0[lane] = self.a0 * xs[lane] + by;
+            e1[lane] = self.a1 * xs[lane] + b1y;
+            e2[lane] = self.a2 * xs[lane] + b2y;
+        }
+        let mut out = [(0.0f32, 0.0f32, 0.0f32); 4];
+        for lane in 0..4 {
+            let (f1, f2) = (e1[lane] * self.inv_area, e2[lane] * self.inv_area);
+            out[lane] = (1.0 - f1 - f2, f1, f2);
+        }
+        out
+    }
+
+    // Companion to `weights_at`: true when every barycentric weight is
+    // non-negative, i.e. the sample point lies inside (or exactly on the
+    // edge of) the triangle. See `CoverageTest::BarycentricInside`.
+    fn is_inside(f: (f32, f32, f32)) -> bool {
+        f.0 >= 0.0 && f.1 >= 0.0 && f.2 >= 0.0
+    }
+}
+
+// Measures wall-clock seconds elapsed between successive `tick()` calls, so
+// animation can advance by `rate * dt` instead of a fixed per-frame step
+// whose visible speed would otherwise depend on how fast the loop spins.
+pub struct Clock {
+    last_tick: std::time::Instant
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock { last_tick: std::time::Instant::now() }
+    }
+
+    pub fn tick(&mut self) -> f32 {
+        let now = std::time::Instant::now();
+        let dt = now.duration_since(self.last_tick).as_secs_f32();
+        self.last_tick = now;
+        dt
+    }
+}
+
+// How many of the most recent frame times `average_ms` smooths over.
+const FRAME_TIME_WINDOW: usize = 30;
+
+pub struct FpsCounter {
+    last_time: std::time::Instant,
+    counter: u32,
+    last_frame_time: std::time::Instant,
+    frame_times_ms: std::collections::VecDeque<f32>
+}
+
+impl Default for FpsCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FpsCounter {
+    pub fn new() -> Self {
+        let now = std::time::Instant::now();
+        FpsCounter {
+            last_time: now,
+            counter: 0,
+            last_frame_time: now,
+            frame_times_ms: std::collections::VecDeque::with_capacity(FRAME_TIME_WINDOW)
+        }
+    }
+
+    pub fn update(&mut self) -> Option<u32> {
+        let now = std::time::Instant::now();
+        self.record_frame_time(now.duration_since(self.last_frame_time));
+        self.last_frame_time = now;
+
+        self.counter += 1;
+        match self.last_time.elapsed().as_millis() {
+            s if s >= 1000 => {
+                let counter = self.counter;
+                self.counter = 0;
+                self.last_time = std::time::Instant::now();
+                Some(counter)
+            },
+            _ => None
+        }
+    }
+
+    fn record_frame_time(&mut self, dt: std::time::Duration) {
+        if self.frame_times_ms.len() == FRAME_TIME_WINDOW {
+            self.frame_times_ms.pop_front();
+        }
+        self.frame_times_ms.push_back(dt.as_secs_f32() * 1000.0);
+    }
+
+    pub fn last_frame_ms(&self) -> f32 {
+        self.frame_times_ms.back().copied().unwrap_or(0.0)
+    }
+
+    pub fn average_ms(&self) -> f32 {
+        if self.frame_times_ms.is_empty() {
+            return 0.0;
+        }
+        self.frame_times_ms.iter().sum::<f32>() / self.frame_times_ms.len() as f32
+    }
+}
+
+// The channel order `TextureBuffer`'s backing bytes are stored in. Shaders
+// always deal in logical RGBA; `set`/`get` reorder to and from whatever the
+// buffer's bytes actually need to be (e.g. to match an SDL texture's native
+// format) so callers never have to think about it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgba,
+    Bgra
+}
+
+impl PixelFormat {
+    // Swapping the red and blue channels is its own inverse, so the same
+    // reorder converts logical RGBA to native bytes and back again.
+    fn reorder(&self, color: [u8; 4]) -> [u8; 4] {
+        match self {
+            PixelFormat::Rgba => color,
+            PixelFormat::Bgra => [color[2], color[1], color[0], color[3]]
+        }
+    }
+}
+
+// Selects the kernel `TextureBuffer::resolve_with_filter` uses to weight
+// supersampled texels into each output texel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResolveFilter {
+    Box,
+    Tent,
+    Gaussian
+}
+
+// Backing storage for a `TextureBuffer`: either an allocation it owns, or a
+// caller-provided slice borrowed via `TextureBuffer::from_slice`, e.g. one
+// already mapped by a windowing/GPU crate like `softbuffer` or `pixels`.
+// Derefs to `[u8]` so callers can keep treating `buffer` like a byte slice
+// regardless of which variant backs it.
+pub enum Buffer<'b> {
+    Owned(Vec<u8>),
+    Borrowed(&'b mut [u8])
+}
+
+impl<'b> std::ops::Deref for Buffer<'b> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Buffer::Owned(v) => v,
+            Buffer::Borrowed(s) => s
+        }
+    }
+}
+
+impl<'b> std::ops::DerefMut for Buffer<'b> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Buffer::Owned(v) => v,
+            Buffer::Borrowed(s) => s
+        }
+    }
+}
+
+pub struct TextureBuffer<'b> {
+    pub buffer: Buffer<'b>,
+    size: (u32, u32),
+    bytes_per_pixel: u32,
+    format: PixelFormat,
+    // Union of every `set`/`set_checked` point since the buffer was last
+    // cleared or `reset_dirty` was called, as (x, y, width, height). Lets a
+    // caller upload only the changed sub-rect of a much larger, mostly
+    // static frame instead of the whole buffer.
+    dirty_rect: Option<(u32, u32, u32, u32)>
+}
+
+impl<'b> TextureBuffer<'b> {
+    pub fn new(size: (u32, u32), bytes_per_pixel: u32) -> Self {
+        Self::new_with_format(size, bytes_per_pixel, PixelFormat::Rgba)
+    }
+
+    pub fn new_with_format(size: (u32, u32), bytes_per_pixel: u32, format: PixelFormat) -> Self {
+        TextureBuffer {
+            buffer: Buffer::Owned(vec![0; (size.0 * size.1 * bytes_per_pixel) as usize]),
+            size,
+            bytes_per_pixel,
+            format,
+            dirty_rect: None
+        }
+    }
+
+    // Wraps a caller-provided slice instead of allocating, so a `TextureBuffer`
+    // can render directly into e.g. a `softbuffer`/`pixels` surface. The slice
+    // must already be exactly `size.0 * size.1 * bytes_per_pixel` bytes long.
+    pub fn from_slice(buffer: &'b mut [u8], size: (u32, u32), bytes_per_pixel: u32) -> Self {
+        Self::from_slice_with_format(buffer, size, bytes_per_pixel, PixelFormat::Rgba)
+    }
+
+    pub fn from_slice_with_format(buffer: &'b mut [u8], size: (u32, u32), bytes_per_pixel: u32, format: PixelFormat) -> Self {
+        let expected_len = (size.0 * size.1 * bytes_per_pixel) as usize;
+        assert_eq!(buffer.len(), expected_len,
+            "TextureBuffer::from_slice buffer of {} bytes does not match size {:?} at {} bytes per pixel",
+            buffer.len(), size, bytes_per_pixel);
+        TextureBuffer {
+            buffer: Buffer::Borrowed(buffer),
+            size,
+            bytes_per_pixel,
+            format,
+            dirty_rect: None
+        }
+    }
+
+    pub fn pitch(&self) -> usize {
+        (self.size.0 * self.bytes_per_pixel) as usize
+    }
+
+    pub fn set(&mut self, point: (u32, u32), color: &[u8; 4]) {
+        debug_assert!(point.0 < self.size.0 && point.1 < self.size.1,
+            "TextureBuffer::set point {:?} is out of bounds for size {:?}", point, self.size);
+        let color = self.format.reorder(*color);
+        let index = (self.bytes_per_pixel * (point.1 * self.size.0 + point.0)) as usize;
+        unsafe {
+            std::ptr::copy_nonoverlapping(color.as_ptr(),
+                self.buffer.as_mut_ptr().add(index),
+                std::mem::size_of_val(&color));
+        }
+        self.dirty_rect = Some(match self.dirty_rect {
+            Some((x, y, width, height)) => {
+                let min_x = x.min(point.0);
+                let min_y = y.min(point.1);
+                let max_x = (x + width).max(point.0 + 1);
+                let max_y = (y + height).max(point.1 + 1);
+                (min_x, min_y, max_x - min_x, max_y - min_y)
+            },
+            None => (point.0, point.1, 1, 1)
+        });
+    }
+
+    // Same as `set`, but validates the point against the buffer's bounds
+    // and returns `false` instead of writing out of range. The parallel
+    // scanline rasterizer writes through raw pointers instead (each row's
+    // bounds are already clamped before the loop), so this remains the
+    // bounds-checked entry point for single-threaded callers and tests.
+    pub fn set_checked(&mut self, point: (u32, u32), color: &[u8; 4]) -> bool {
+        if point.0 >= self.size.0 || point.1 >= self.size.1 {
+            return false;
+        }
+        self.set(point, color);
+        true
+    }
+
+    pub fn get(&self, point: (u32, u32)) -> [u8; 4] {
+        let index = (self.bytes_per_pixel * (point.1 * self.size.0 + point.0)) as usize;
+        let raw = [self.buffer[index], self.buffer[index + 1], self.buffer[index + 2], self.buffer[index + 3]];
+        self.format.reorder(raw)
+    }
+
+    pub fn clear(&mut self, value: u8) {
+        for v in self.buffer.iter_mut() {
+            *v = value;
+        }
+        self.reset_dirty();
+    }
+
+    // Fills the buffer with a vertical lerp from `top` (row 0) to `bottom`
+    // (the last row), for a cheap sky-gradient background cleared before
+    // scene geometry instead of a flat color. A full skybox - six `Texture`s
+    // sampled per-pixel by the inverse view-projection ray direction - is a
+    // natural follow-on once a caller needs more than a flat gradient.
+    pub fn clear_gradient(&mut self, top: [u8; 4], bottom: [u8; 4]) {
+        let height = self.size.1;
+        for y in 0..height {
+            let t = if height > 1 { y as f32 / (height - 1) as f32 } else { 0.0 };
+            let color = [
+                (top[0] as f32 + (bottom[0] as f32 - top[0] as f32) * t).round() as u8,
+                (top[1] as f32 + (bottom[1] as f32 - top[1] as f32) * t).round() as u8,
+                (top[2] as f32 + (bottom[2] as f32 - top[2] as f32) * t).round() as u8,
+                (top[3] as f32 + (bottom[3] as f32 - top[3] as f32) * t).round() as u8
+            ];
+            for x in 0..self.size.0 {
+                self.set((x, y), &color);
+            }
+        }
+    }
+
+    // The union of every `set`/`set_checked` point since the buffer was
+    // last cleared or `reset_dirty` was called, as (x, y, width, height).
+    // `None` means nothing has been written yet.
+    pub fn dirty_rect(&self) -> Option<(u32, u32, u32, u32)> {
+        self.dirty_rect
+    }
+
+    // Forgets the accumulated dirty rect without touching the buffer's
+    // contents, e.g. once a caller has finished uploading it and wants to
+    // start tracking the next frame's writes.
+    pub fn reset_dirty(&mut self) {
+        self.dirty_rect = None;
+    }
+
+    // Reallocates the buffer to `size`, e.g. in response to a window resize;
+    // the old contents are discarded rather than resampled, same as `new`.
+    // Panics for a `from_slice`-backed buffer, since its storage is owned by
+    // the caller and can't be grown or shrunk here.
+    pub fn resize(&mut self, size: (u32, u32)) {
+        assert!(matches!(self.buffer, Buffer::Owned(_)),
+            "TextureBuffer::resize cannot reallocate a from_slice-backed buffer");
+        self.size = size;
+        self.buffer = Buffer::Owned(vec![0; (size.0 * size.1 * self.bytes_per_pixel) as usize]);
+    }
+
+    // Converts the buffer to a flat RGBA byte vector, reordering channels
+    // first if `format` isn't already RGBA (e.g. the BGRA a window surface
+    // often wants). Shared by `save_png` and `GifRecorder::capture`, both of
+    // which need this exact layout regardless of the buffer's own format.
+    pub fn to_rgba_frame(&self) -> Vec<u8> {
+        match self.format {
+            PixelFormat::Rgba => self.buffer.to_vec(),
+            _ => self.buffer.chunks_exact(4)
+                .flat_map(|p| self.format.reorder([p[0], p[1], p[2], p[3]]))
+                .collect()
+        }
+    }
+
+    // Encodes the buffer as a PNG, assuming it's laid out as 4-byte-per-pixel
+    // (the only layout the `image` crate's `RgbaImage` can be built from
+    // without a copy into a different format); a non-RGBA `format` is
+    // reordered into RGBA first.
+    pub fn save_png(&self, path: &str) -> Result<(), std::io::Error> {
+        if self.bytes_per_pixel != 4 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("TextureBuffer::save_png requires 4 bytes per pixel, got {}", self.bytes_per_pixel)
+            ));
+        }
+
+        let image = image::RgbaImage::from_raw(self.size.0, self.size.1, self.to_rgba_frame())
+            .expect("buffer length already matches size.0 * size.1 * bytes_per_pixel");
+        image.save(path).map_err(std::io::Error::other)
+    }
+
+    // Box-downsamples by `factor` per axis, averaging each `factor`x`factor`
+    // block of channels into one output texel. Used to resolve a buffer that
+    // was rendered at a higher resolution than the display (ordered-grid MSAA).
+    pub fn resolve(&self, factor: u32) -> TextureBuffer<'static> {
+        let out_size = (self.size.0 / factor, self.size.1 / factor);
+        let mut out = TextureBuffer::new_with_format(out_size, self.bytes_per_pixel, self.format);
+
+        for y in 0..out_size.1 {
+            for x in 0..out_size.0 {
+                let mut sum = [0u32; 4];
+                for sy in 0..factor {
+                    for sx in 0..factor {
+                        let sample = self.get((x * factor + sx, y * factor + sy));
+                        for c in 0..4 {
+                            sum[c] += sample[c] as u32;
+                        }
+                    }
+                }
+                let samples = factor * factor;
+                let averaged = [
+                    (sum[0] / samples) as u8,
+                    (sum[1] / samples) as u8,
+                    (sum[2] / samples) as u8,
+                    (sum[3] / samples) as u8
+                ];
+                out.set((x, y), &averaged);
+            }
+        }
+
+        out
+    }
+
+    // Like `resolve`, but lets the caller pick the kernel used to weight
+    // supersampled texels into each output texel. `Box` is exactly `resolve`;
+    // `Tent` and `Gaussian` widen the box's `factor`x`factor` block by one
+    // texel of margin on each side (clamped to the buffer edge) and weight
+    // samples by their distance from the block's center, trading a touch of
+    // blur for less aliasing on high-contrast edges. A quality knob for
+    // screenshot-grade output, where `resolve`'s plain box average is fine
+    // for real-time frames but leaves visible stairstepping on stills.
+    pub fn resolve_with_filter(&self, factor: u32, filter: ResolveFilter) -> TextureBuffer<'static> {
+        if filter == ResolveFilter::Box {
+            return self.resolve(factor);
+        }
+
+        let out_size = (self.size.0 / factor, self.size.1 / factor);
+        let mut out = TextureBuffer::new_with_format(out_size, self.bytes_per_pixel, self.format);
+
+        const MARGIN: i64 = 1;
+        let radius = factor as f32 / 2.0 + MARGIN as f32;
+        let sigma = factor as f32 / 2.0;
+
+        for y in 0..out_size.1 {
+            for x in 0..out_size.0 {
+                let center_x = x as f32 * factor as f32 + (factor as f32 - 1.0) / 2.0;
+                let center_y = y as f32 * factor as f32 + (factor as f32 - 1.0) / 2.0;
+                let lo_x = (x * factor) as i64 - MARGIN;
+                let hi_x = (x * factor + factor - 1) as i64 + MARGIN;
+                let lo_y = (y * factor) as i64 - MARGIN;
+                let hi_y = (y * factor + factor - 1) as i64 + MARGIN;
+
+                let mut sum = [0f32; 4];
+                let mut weight_sum = 0f32;
+                for sy in lo_y..=hi_y {
+                    if sy < 0 || sy >= self.size.1 as i64 {
+                        continue;
+                    }
+                    for sx in lo_x..=hi_x {
+                        if sx < 0 || sx >= self.size.0 as i64 {
+                            continue;
+                        }
+                        let dx = sx as f32 - center_x;
+                        let dy = sy as f32 - center_y;
+                        let weight = match filter {
+                            ResolveFilter::Box => unreachable!("handled by the early return above"),
+                            ResolveFilter::Tent =>
+                                (1.0 - dx.abs() / radius).max(0.0) * (1.0 - dy.abs() / radius).max(0.0),
+                            ResolveFilter::Gaussian =>
+                                (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp()
+                        };
+                        let sample = self.get((sx as u32, sy as u32));
+                        for c in 0..4 {
+                            sum[c] += sample[c] as f32 * weight;
+                        }
+                        weight_sum += weight;
+                    }
+                }
+                let averaged = [
+                    (sum[0] / weight_sum).round() as u8,
+                    (sum[1] / weight_sum).round() as u8,
+                    (sum[2] / weight_sum).round() as u8,
+                    (sum[3] / weight_sum).round() as u8
+                ];
+                out.set((x, y), &averaged);
+            }
+        }
+
+        out
+    }
+}
+
+// Advance in pixels from one character's cell to the next in `draw_text`.
+// Each glyph only lights up its leftmost 5 columns of an 8-wide cell; the
+// remaining 3 columns are the inter-character gap baked into the cell so
+// callers don't need to add their own spacing.
+const GLYPH_CELL: (u32, u32) = (8, 8);
+
+// Row bitmaps for the embedded bitmap font `draw_text` plots into a
+// `TextureBuffer`. Each glyph is 8 rows of 8 columns, with the pixels
+// packed into the top 5 bits of each byte (bit 7 = leftmost column) and
+// the bottom row left blank; only the characters the on-screen HUD in
+// `main.rs` actually prints are defined, not a full ASCII table.
+fn glyph_rows(c: char) -> Option<[u8; 8]> {
+    Some(match c {
+        '0' => [0x70, 0x88, 0x98, 0xA8, 0xC8, 0x88, 0x70, 0x00],
+        '1' => [0x20, 0x60, 0x20, 0x20, 0x20, 0x20, 0x70, 0x00],
+        '2' => [0x70, 0x88, 0x08, 0x10, 0x20, 0x40, 0xF8, 0x00],
+        '3' => [0xF8, 0x10, 0x20, 0x10, 0x08, 0x88, 0x70, 0x00],
+        '4' => [0x10, 0x30, 0x50, 0x90, 0xF8, 0x10, 0x10, 0x00],
+        '5' => [0xF8, 0x80, 0xF0, 0x08, 0x08, 0x88, 0x70, 0x00],
+        '6' => [0x30, 0x40, 0x80, 0xF0, 0x88, 0x88, 0x70, 0x00],
+        '7' => [0xF8, 0x08, 0x10, 0x20, 0x40, 0x40, 0x40, 0x00],
+        '8' => [0x70, 0x88, 0x88, 0x70, 0x88, 0x88, 0x70, 0x00],
+        '9' => [0x70, 0x88, 0x88, 0x78, 0x08, 0x10, 0x60, 0x00],
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        ':' => [0x00, 0x20, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x00],
+        '(' => [0x10, 0x20, 0x40, 0x40, 0x40, 0x20, 0x10, 0x00],
+        ')' => [0x40, 0x20, 0x10, 0x10, 0x10, 0x20, 0x40, 0x00],
+        'F' => [0xF8, 0x80, 0x80, 0xF0, 0x80, 0x80, 0x80, 0x00],
+        'p' => [0xF0, 0x88, 0x88, 0xF0, 0x80, 0x80, 0x80, 0x00],
+        's' => [0x78, 0x80, 0x80, 0x70, 0x08, 0x08, 0xF0, 0x00],
+        'a' => [0x00, 0x70, 0x08, 0x78, 0x88, 0x88, 0x78, 0x00],
+        'v' => [0x88, 0x88, 0x88, 0x88, 0x88, 0x50, 0x20, 0x00],
+        'g' => [0x78, 0x88, 0x88, 0x78, 0x08, 0x88, 0x70, 0x00],
+        'm' => [0x00, 0xD8, 0xA8, 0xA8, 0xA8, 0xA8, 0x88, 0x00],
+        _ => return None
+    })
+}
+
+// Draws `text` into `target` with its top-left corner at `(x, y)`, one
+// `GLYPH_CELL`-sized cell per character, via the embedded bitmap font in
+// `glyph_rows`. Lets a HUD (e.g. the fps/frame-time overlay in `main.rs`)
+// be baked straight into the framebuffer instead of only reaching stdout.
+// Characters outside the embedded font (and any glyph pixel that would
+// land outside `target`) are silently skipped rather than panicking, same
+// as `set_checked`.
+pub fn draw_text(target: &mut TextureBuffer, x: u32, y: u32, text: &str, color: &[u8; 4]) {
+    for (i, ch) in text.chars().enumerate() {
+        let rows = match glyph_rows(ch) {
+            Some(rows) => rows,
+            None => continue
+        };
+        let cell_x = x + i as u32 * GLYPH_CELL.0;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_CELL.0 {
+                if bits & (0x80 >> col) != 0 {
+                    target.set_checked((cell_x + col, y + row as u32), color);
+                }
+            }
+        }
+    }
+}
+
+// Per-pixel statistics from `compare_images`, letting a golden-image test
+// report more than "images differ" when a regression sneaks into the
+// rasterization math.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffStats {
+    pub differing_pixels: u32,
+    pub max_channel_diff: u8
+}
+
+// Compares `a` and `b` pixel-by-pixel, treating a per-channel difference of
+// at most `tolerance` as a match, so a golden-image comparison survives
+// harmless platform/float rounding differences instead of demanding
+// byte-identical output. Returns `None` when every pixel is within
+// tolerance, `Some(DiffStats)` otherwise. Panics if the two buffers differ
+// in size, since there's no sensible pixel-to-pixel correspondence to
+// compare in that case.
+pub fn compare_images(a: &TextureBuffer, b: &TextureBuffer, tolerance: u8) -> Option<DiffStats> {
+    assert_eq!(a.size, b.size, "compare_images requires equally sized buffers, got {:?} and {:?}", a.size, b.size);
+
+    let mut differing_pixels = 0;
+    let mut max_channel_diff = 0u8;
+    for y in 0..a.size.1 {
+        for x in 0..a.size.0 {
+            let pa = a.get((x, y));
+            let pb = b.get((x, y));
+            let mut pixel_differs = false;
+            for channel in 0..4 {
+                let diff = pa[channel].abs_diff(pb[channel]);
+                max_channel_diff = max_channel_diff.max(diff);
+                if diff > tolerance {
+                    pixel_differs = true;
+                }
+            }
+            if pixel_differs {
+                differing_pixels += 1;
+            }
+        }
+    }
+
+    if differing_pixels == 0 {
+        None
+    } else {
+        Some(DiffStats { differing_pixels, max_channel_diff })
+    }
+}
+
+// Captures presented frames into an animated GIF, e.g. toggled by a key in
+// `main` and dropped (finalizing the file) on exit. Frames are capped to
+// `max_fps` rather than captured every call, since a GIF's minimum frame
+// delay is coarser than most render loops and encoding every frame would
+// both bloat the file and slow it down when played back.
+pub struct GifRecorder {
+    encoder: gif::Encoder<std::fs::File>,
+    frame_interval: std::time::Duration,
+    last_capture: Option<std::time::Instant>
+}
+
+impl GifRecorder {
+    pub fn new(path: &str, size: (u32, u32), max_fps: f32) -> Result<Self, std::io::Error> {
+        let file = std::fs::File::create(path)?;
+        let mut encoder = gif::Encoder::new(file, size.0 as u16, size.1 as u16, &[])
+            .map_err(std::io::Error::other)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(std::io::Error::other)?;
+        Ok(GifRecorder {
+            encoder,
+            frame_interval: std::time::Duration::from_secs_f32(1.0 / max_fps),
+            last_capture: None
+        })
+    }
+
+    // Encodes `target` as the next frame, unless `max_fps` says it's too
+    // soon since the last one, in which case this is a silent no-op. Reuses
+    // `to_rgba_frame`'s BGRA-to-RGBA handling so a `format`-tagged buffer
+    // (e.g. one wrapping a window surface) still comes out right.
+    pub fn capture(&mut self, target: &TextureBuffer) -> Result<(), std::io::Error> {
+        let now = std::time::Instant::now();
+        if let Some(last_capture) = self.last_capture {
+            if now - last_capture < self.frame_interval {
+                return Ok(());
+            }
+        }
+        self.last_capture = Some(now);
+
+        let mut rgba = target.to_rgba_frame();
+        let mut frame = gif::Frame::from_rgba(target.size.0 as u16, target.size.1 as u16, &mut rgba);
+        frame.delay = (self.frame_interval.as_secs_f32() * 100.0).round() as u16;
+        self.encoder.write_frame(&frame).map_err(std::io::Error::other)
+    }
+}
+
+// Holds two `TextureBuffer`s of identical size/format so a render can draw
+// into the one not currently being presented; `swap` then exchanges them in
+// constant time instead of copying pixels.
+pub struct DoubleBuffer {
+    buffers: [TextureBuffer<'static>; 2],
+    front: usize
+}
+
+impl DoubleBuffer {
+    pub fn new(size: (u32, u32), bytes_per_pixel: u32) -> Self {
+        Self::new_with_format(size, bytes_per_pixel, PixelFormat::Rgba)
+    }
+
+    pub fn new_with_format(size: (u32, u32), bytes_per_pixel: u32, format: PixelFormat) -> Self {
+        DoubleBuffer {
+            buffers: [
+                TextureBuffer::new_with_format(size, bytes_per_pixel, format),
+                TextureBuffer::new_with_format(size, bytes_per_pixel, format)
+            ],
+            front: 0
+        }
+    }
+
+    pub fn front(&self) -> &TextureBuffer<'static> {
+        &self.buffers[self.front]
+    }
+
+    pub fn back_mut(&mut self) -> &mut TextureBuffer<'static> {
+        &mut self.buffers[1 - self.front]
+    }
+
+    pub fn swap(&mut self) {
+        self.front = 1 - self.front;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthFunc {
+    Less,
+    LessEqual,
+    Greater,
+    Equal,
+    Always
+}
+
+impl DepthFunc {
+    fn passes(&self, new_depth: f32, old_depth: f32) -> bool {
+        match self {
+            DepthFunc::Less => new_depth < old_depth,
+            DepthFunc::LessEqual => new_depth <= old_depth,
+            DepthFunc::Greater => new_depth > old_depth,
+            DepthFunc::Equal => new_depth == old_depth,
+            DepthFunc::Always => true
+        }
+    }
+}
+
+// Which convention `transform_to_target_coordinates` maps NDC z into before
+// it's stored in the depth buffer. nalgebra-glm's `perspective`/`ortho`
+// produce OpenGL-style `[-1, 1]` NDC z, which is what `DepthBuffer` has
+// always stored as-is; `ZeroToOne` remaps it to the `[0, 1]` convention
+// some other pipelines (and `glm::perspective_zo`-style projections) expect.
+//
+// `ReverseZeroToOne` maps to the same `[0, 1]` range but with the ends
+// swapped (near plane -> 1, far plane -> 0), paired with `DepthFunc::Greater`
+// and a `DepthBuffer` cleared to `0.0`. Floating-point depth values are only
+// ever precise to a fixed number of *significant* digits, and the standard
+// mapping squeezes the entire far half of the frustum into a narrow band
+// right below `1.0`, where the available precision is coarsest - reversing
+// the mapping puts that same squeezed range down near `0.0` instead, where
+// floats have far more precision to spend, at no extra cost since the
+// underlying camera projection doesn't change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthRange {
+    NegativeOneToOne,
+    ZeroToOne,
+    ReverseZeroToOne
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    Opaque,
+    AlphaBlend,
+    Additive
+}
+
+impl BlendMode {
+    // Combines a freshly-shaded `src` color with the existing `dst` pixel,
+    // treating `src`'s alpha channel as the blend factor.
+    fn blend(&self, src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
+        match self {
+            BlendMode::Opaque => src,
+            BlendMode::AlphaBlend => {
+                let a = src[3] as f32 / 255.0;
+                let mut out = [0u8; 4];
+                for i in 0..4 {
+                    out[i] = (src[i] as f32 * a + dst[i] as f32 * (1.0 - a)).round() as u8;
+                }
+                out
+            },
+            BlendMode::Additive => {
+                let mut out = [0u8; 4];
+                for i in 0..4 {
+                    out[i] = (src[i] as u16 + dst[i] as u16).min(255) as u8;
+                }
+                out
+            }
+        }
+    }
+}
+
+// Which geometric test decides a triangle's facing direction for `CullMode`.
+// `ScreenSpace` is the original 2D cross product over post-projection x/y;
+// it's cheap but only sees what survives the projection, which an
+// orthographic camera can collapse in ways that make the sign unreliable.
+// `ViewSpaceNormal` instead cross-products the true pre-projection edge
+// vectors (see `RenderContext::with_view_space_normal_cull`) and tests the
+// resulting face normal against the camera's view direction directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMethod {
+    ScreenSpace,
+    ViewSpaceNormal
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RasterBackend {
+    Scanline,
+    Tiled { tile_size: u32 },
+    // Per-triangle integer-bounding-box scan with three edge-function tests
+    // per pixel (see `RenderContext::draw_triangle_edge_function`), rather
+    // than `Scanline`'s flat-top/flat-bottom split. Uses the same
+    // `covers_with_top_left_rule` fill rule as `Scanline`'s default
+    // `CoverageTest::TopLeftRule`, so the two agree on coverage pixel-for-
+    // pixel; unlike `Tiled` it doesn't bin triangles into tiles first.
+    EdgeFunction
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FogMode {
+    Linear,
+    Exponential
+}
+
+// Blends the shaded fragment color toward `color` based on the fragment's
+// interpolated depth, the same value already being computed for depth
+// testing. `start`/`end` bound `Linear` fog; `density` controls how quickly
+// `Exponential` fog thickens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fog {
+    pub color: [u8; 4],
+    pub mode: FogMode,
+    pub start: f32,
+    pub end: f32,
+    pub density: f32
+}
+
+impl Fog {
+    fn factor(&self, depth: f32) -> f32 {
+        match self.mode {
+            FogMode::Linear => ((depth - self.start) / (self.end - self.start)).clamp(0.0, 1.0),
+            FogMode::Exponential => (1.0 - (-self.density * depth).exp()).clamp(0.0, 1.0)
+        }
+    }
+
+    fn apply(&self, color: [u8; 4], depth: f32) -> [u8; 4] {
+        let factor = self.factor(depth);
+        let mut out = color;
+        for i in 0..3 {
+            out[i] = (color[i] as f32 * (1.0 - factor) + self.color[i] as f32 * factor).round() as u8;
+        }
+        out
+    }
+}
+
+pub struct DepthBuffer {
+    buffer: Vec<f32>,
+    size: (u32, u32)
+}
+
+impl DepthBuffer {
+    pub fn new(size: (u32, u32)) -> Self {
+        DepthBuffer {
+            buffer: vec![f32::INFINITY; (size.0 * size.1) as usize],
+            size
+        }
+    }
+
+    pub fn clear_depth(&mut self, value: f32) {
+        for v in &mut self.buffer {
+            *v = value;
+        }
+    }
+
+    // The parallel scanline rasterizer indexes `buffer` directly through a
+    // raw pointer instead of calling these, but they remain the safe,
+    // single-threaded entry points (and are exercised by tests).
+    pub fn get(&self, point: (u32, u32)) -> f32 {
+        self.buffer[(point.1 * self.size.0 + point.0) as usize]
+    }
+
+    pub fn set(&mut self, point: (u32, u32), value: f32) {
+        self.buffer[(point.1 * self.size.0 + point.0) as usize] = value;
+    }
+}
+
+// A mip pyramid of per-tile *maximum* depths built from a `DepthBuffer`'s
+// current contents, so `RenderContext::with_hierarchical_z` can reject a
+// whole triangle before rasterizing a single pixel of it when it's
+// guaranteed to lose the depth test everywhere it could land. Level 0 is a
+// direct copy of the depth buffer; each level after that halves both
+// dimensions (rounding up) by taking the max, not the average, of each 2x2
+// block below it, so every stored value stays a safe upper bound on how far
+// back anything already drawn in that tile actually is.
+pub struct HierarchicalDepthBuffer {
+    levels: Vec<Vec<f32>>,
+    sizes: Vec<(u32, u32)>
+}
+
+impl HierarchicalDepthBuffer {
+    pub fn build(depth: &DepthBuffer) -> Self {
+        let mut levels = vec![depth.buffer.clone()];
+        let mut sizes = vec![depth.size];
+
+        loop {
+            let &(prev_w, prev_h) = sizes.last().unwrap();
+            if prev_w <= 1 && prev_h <= 1 {
+                break;
+            }
+            let prev_buffer = levels.last().unwrap();
+            let (w, h) = (prev_w.div_ceil(2), prev_h.div_ceil(2));
+            let mut next = vec![f32::NEG_INFINITY; (w * h) as usize];
+            for y in 0..prev_h {
+                for x in 0..prev_w {
+                    let idx = ((y / 2) * w + (x / 2)) as usize;
+                    next[idx] = next[idx].max(prev_buffer[(y * prev_w + x) as usize]);
+                }
+            }
+            levels.push(next);
+            sizes.push((w, h));
+        }
+
+        HierarchicalDepthBuffer { levels, sizes }
+    }
+
+    // The maximum stored depth over the screen-space rect `[x0, x1) x [y0,
+    // y1)`, i.e. an upper bound on how far back anything actually drawn
+    // there is. Picks the coarsest level whose tiles aren't bigger than the
+    // rect itself, so the lookup only ever touches a handful of texels.
+    fn max_depth_over(&self, x0: u32, y0: u32, x1: u32, y1: u32) -> f32 {
+        if x1 <= x0 || y1 <= y0 {
+            return f32::NEG_INFINITY;
+        }
+        let extent = (x1 - x0).max(y1 - y0).max(1);
+        let level = (extent.next_power_of_two().trailing_zeros() as usize).min(self.levels.len() - 1);
+        let (w, _) = self.sizes[level];
+        let buffer = &self.levels[level];
+        let (tx0, ty0) = (x0 >> level, y0 >> level);
+        let (tx1, ty1) = ((x1 - 1) >> level, (y1 - 1) >> level);
+
+        let mut max_depth = f32::NEG_INFINITY;
+        for ty in ty0..=ty1 {
+            for tx in tx0..=tx1 {
+                max_depth = max_depth.max(buffer[(ty * w + tx) as usize]);
+            }
+        }
+        max_depth
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StencilFunc {
+    Always,
+    Equal,
+    NotEqual
+}
+
+impl StencilFunc {
+    fn passes(&self, reference: u8, stencil_value: u8) -> bool {
+        match self {
+            StencilFunc::Always => true,
+            StencilFunc::Equal => reference == stencil_value,
+            StencilFunc::NotEqual => reference != stencil_value
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StencilOp {
+    Keep,
+    Replace,
+    Increment,
+    Zero
+}
+
+impl StencilOp {
+    fn apply(&self, old_value: u8, reference: u8) -> u8 {
+        match self {
+            StencilOp::Keep => old_value,
+            StencilOp::Replace => reference,
+            StencilOp::Increment => old_value.saturating_add(1),
+            StencilOp::Zero => 0
+        }
+    }
+}
+
+// Parallels `DepthBuffer`: an 8-bit-per-pixel buffer a `RenderContext` can
+// test and update per fragment, most commonly to mask later draws to a
+// region painted by an earlier one (portals, mirrors, decals).
+pub struct StencilBuffer {
+    buffer: Vec<u8>,
+    size: (u32, u32)
+}
+
+impl StencilBuffer {
+    pub fn new(size: (u32, u32)) -> Self {
+        StencilBuffer {
+            buffer: vec![0; (size.0 * size.1) as usize],
+            size
+        }
+    }
+
+    pub fn clear_stencil(&mut self, value: u8) {
+        for v in &mut self.buffer {
+            *v = value;
+        }
+    }
+
+    pub fn get(&self, point: (u32, u32)) -> u8 {
+        self.buffer[(point.1 * self.size.0 + point.0) as usize]
+    }
+
+    pub fn set(&mut self, point: (u32, u32), value: u8) {
+        self.buffer[(point.1 * self.size.0 + point.0) as usize] = value;
+    }
+}
+
+// Tracks how many times each pixel has passed rasterization (regardless of
+// whether the depth test then rejected it), for finding expensive overdraw
+// to reorder draws around or add a depth prepass for. Attached to a
+// `RenderContext` via `with_overdraw_tracking`; call `resolve_heatmap` at
+// present time to turn the raw counts into a viewable image.
+pub struct OverdrawBuffer {
+    buffer: Vec<u16>,
+    size: (u32, u32)
+}
+
+impl OverdrawBuffer {
+    pub fn new(size: (u32, u32)) -> Self {
+        OverdrawBuffer {
+            buffer: vec![0; (size.0 * size.1) as usize],
+            size
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for v in &mut self.buffer {
+            *v = 0;
+        }
+    }
+
+    pub fn get(&self, point: (u32, u32)) -> u16 {
+        self.buffer[(point.1 * self.size.0 + point.0) as usize]
+    }
+
+    // A single pass is black, climbing through blue, green and yellow to hot
+    // red by the 5th pass over the same pixel.
+    fn heatmap_color(count: u16) -> [u8; 4] {
+        if count == 0 {
+            return [0, 0, 0, 255];
+        }
+        let t = ((count - 1) as f32 / 4.0).clamp(0.0, 1.0);
+        let r = (t * 255.0) as u8;
+        let g = ((1.0 - (t - 0.5).abs() * 2.0).clamp(0.0, 1.0) * 255.0) as u8;
+        let b = ((1.0 - t) * 255.0) as u8;
+        [r, g, b, 255]
+    }
+
+    pub fn resolve_heatmap(&self) -> TextureBuffer<'static> {
+        let mut out = TextureBuffer::new(self.size, 4);
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                out.set((x, y), &Self::heatmap_color(self.get((x, y))));
+            }
+        }
+        out
+    }
+}
+
+// Parallels `DepthBuffer`/`StencilBuffer`: a `u32`-per-pixel buffer a
+// `RenderContext` writes an object id into for every fragment it shades,
+// via `with_id_buffer`. Querying `pick` afterwards answers "what's under
+// this screen pixel?" (e.g. which cube face the mouse clicked) without a
+// separate offscreen pass. `0` is reserved to mean "nothing has drawn
+// here"; ids passed to `with_id_buffer` should start at `1`.
+pub struct IdBuffer {
+    buffer: Vec<u32>,
+    size: (u32, u32)
+}
+
+impl IdBuffer {
+    pub fn new(size: (u32, u32)) -> Self {
+        IdBuffer {
+            buffer: vec![0; (size.0 * size.1) as usize],
+            size
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for v in &mut self.buffer {
+            *v = 0;
+        }
+    }
+
+    pub fn get(&self, point: (u32, u32)) -> u32 {
+        self.buffer[(point.1 * self.size.0 + point.0) as usize]
+    }
+
+    // `None` if no draw call has shaded a fragment at `point` since the
+    // buffer was last cleared, otherwise the id that last did.
+    pub fn pick(&self, point: (u32, u32)) -> Option<u32> {
+        match self.get(point) {
+            0 => None,
+            id => Some(id)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    Nearest,
+    Bilinear
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WrapMode {
+    Clamp,
+    Repeat,
+    Mirror
+}
+
+impl WrapMode {
+    fn apply(&self, v: f32) -> f32 {
+        match self {
+            WrapMode::Clamp => v.clamp(0.0, 1.0),
+            WrapMode::Repeat => v - v.floor(),
+            WrapMode::Mirror => {
+                let folded = v.rem_euclid(2.0);
+                if folded <= 1.0 { folded } else { 2.0 - folded }
+            }
+        }
+    }
+}
+
+pub struct Texture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    // Populated by `generate_mipmaps`; level 0 is the base image, each
+    // subsequent level is half the width and height of the previous one
+    // (floored, so an odd dimension shrinks by dropping its last texel
+    // rather than padding), ending at a 1x1 level.
+    mip_levels: Vec<(u32, u32, Vec<u8>)>,
+    filter: FilterMode,
+    wrap_u: WrapMode,
+    wrap_v: WrapMode,
+    // How many samples `sample_anisotropic` takes along a minification's
+    // longer UV-derivative axis; 1 (the default) makes it equivalent to
+    // `sample_with_lod`.
+    max_anisotropy: u32
+}
+
+impl Texture {
+    pub fn load(path: &str) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Ok(Texture {
+            width,
+            height,
+            pixels: image.into_raw(),
+            mip_levels: Vec::new(),
+            filter: FilterMode::Nearest,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        })
+    }
+
+    // Like `load`, but decodes an already-in-memory PNG/JPEG/etc buffer
+    // (e.g. `include_bytes!`'d into the binary) instead of reading a path,
+    // so an embedded asset never touches the filesystem at all.
+    pub fn from_encoded_bytes(bytes: &[u8]) -> Result<Self, image::ImageError> {
+        let image = image::load_from_memory(bytes)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        Ok(Texture {
+            width,
+            height,
+            pixels: image.into_raw(),
+            mip_levels: Vec::new(),
+            filter: FilterMode::Nearest,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        })
+    }
+
+    // Builds a texture from caller-supplied RGBA pixels instead of decoding
+    // an image file, so texturing can be exercised in tests/examples with no
+    // I/O. `data` must be exactly `width * height * 4` bytes, row-major,
+    // top-left origin, matching what `load` produces.
+    pub fn from_raw(width: u32, height: u32, data: Vec<u8>) -> Self {
+        debug_assert_eq!(data.len(), (width * height * 4) as usize,
+            "Texture::from_raw expected {} bytes for a {}x{} RGBA buffer, got {}",
+            width * height * 4, width, height, data.len());
+        Texture {
+            width,
+            height,
+            pixels: data,
+            mip_levels: Vec::new(),
+            filter: FilterMode::Nearest,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        }
+    }
+
+    pub fn solid(width: u32, height: u32, color: [u8; 4]) -> Self {
+        let pixels = color.iter().copied().cycle().take((width * height * 4) as usize).collect();
+        Self::from_raw(width, height, pixels)
+    }
+
+    // A two-color checkerboard with `cell`x`cell` squares, starting with `a`
+    // at the top-left.
+    pub fn checkerboard(width: u32, height: u32, cell: u32, a: [u8; 4], b: [u8; 4]) -> Self {
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let color = if ((x / cell) + (y / cell)).is_multiple_of(2) { a } else { b };
+                let index = ((y * width + x) * 4) as usize;
+                pixels[index..index + 4].copy_from_slice(&color);
+            }
+        }
+        Self::from_raw(width, height, pixels)
+    }
+
+    // Builds the mip chain by box-filtering each level down from the one
+    // above it, stopping once a 1x1 level has been produced. Level 0 is a
+    // copy of the base image, so `mip_levels[0]` and `(self.width, self.height,
+    // &self.pixels)` always agree.
+    pub fn generate_mipmaps(&mut self) {
+        self.mip_levels.clear();
+        self.mip_levels.push((self.width, self.height, self.pixels.clone()));
+
+        let (mut width, mut height, mut data) = (self.width, self.height, self.pixels.clone());
+        while width > 1 || height > 1 {
+            let next_width = (width / 2).max(1);
+            let next_height = (height / 2).max(1);
+            let mut next_data = vec![0u8; (next_width * next_height * 4) as usize];
+
+            for y in 0..next_height {
+                for x in 0..next_width {
+                    let x0 = (x * 2).min(width - 1);
+                    let x1 = (x * 2 + 1).min(width - 1);
+                    let y0 = (y * 2).min(height - 1);
+                    let y1 = (y * 2 + 1).min(height - 1);
+                    let texel_at = |tx: u32, ty: u32, channel: usize| -> u32 {
+                        data[((ty * width + tx) * 4) as usize + channel] as u32
+                    };
+                    for channel in 0..4 {
+                        let sum = texel_at(x0, y0, channel) + texel_at(x1, y0, channel)
+                            + texel_at(x0, y1, channel) + texel_at(x1, y1, channel);
+                        next_data[((y * next_width + x) * 4) as usize + channel] = (sum / 4) as u8;
+                    }
+                }
+            }
+
+            self.mip_levels.push((next_width, next_height, next_data.clone()));
+            width = next_width;
+            height = next_height;
+            data = next_data;
+        }
+    }
+
+    fn texel_in(width: u32, pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * width + x) * 4) as usize;
+        [pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3]]
+    }
+
+    // Dispatches to the configured `FilterMode`, with the UV origin at the top-left.
+    pub fn sample(&self, uv: glm::Vec2) -> [u8; 4] {
+        self.sample_level(self.width, self.height, &self.pixels, uv)
+    }
+
+    // Picks the mip level nearest to `lod` (0 is the base image, higher
+    // levels are progressively smaller) and samples it with the configured
+    // filter and wrap modes. Falls back to `sample` when no mip chain has
+    // been generated yet.
+    fn sample_with_lod(&self, uv: glm::Vec2, lod: f32) -> [u8; 4] {
+        if self.mip_levels.is_empty() {
+            return self.sample(uv);
+        }
+        let level = (lod.round().max(0.0) as usize).min(self.mip_levels.len() - 1);
+        let (width, height, pixels) = &self.mip_levels[level];
+        self.sample_level(*width, *height, pixels, uv)
+    }
+
+    // Estimates a mip level from screen-space UV derivatives, using the
+    // standard "largest texel footprint" rule: the faster the UV changes
+    // per pixel, the more minified the texture is and the coarser the level.
+    fn mip_level_for_derivative(&self, ddx: glm::Vec2, ddy: glm::Vec2) -> f32 {
+        let texel_dx = (ddx.x * self.width as f32).abs().max((ddx.y * self.height as f32).abs());
+        let texel_dy = (ddy.x * self.width as f32).abs().max((ddy.y * self.height as f32).abs());
+        texel_dx.max(texel_dy).max(1.0).log2().max(0.0)
+    }
+
+    // Caps how many samples `sample_anisotropic` takes along the longer
+    // derivative axis; 1 disables the extra sampling entirely.
+    pub fn with_max_anisotropy(mut self, max_anisotropy: u32) -> Self {
+        self.max_anisotropy = max_anisotropy.max(1);
+        self
+    }
+
+    // Selects how `sample`/`sample_with_lod`/`sample_anisotropic` resolve a
+    // UV that falls between texels; defaults to `FilterMode::Nearest`.
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    // Selects how `sample`/`sample_with_lod`/`sample_anisotropic` resolve a
+    // UV outside `[0, 1]`, independently per axis; defaults to
+    // `WrapMode::Clamp` on both axes.
+    pub fn with_wrap(mut self, wrap_u: WrapMode, wrap_v: WrapMode) -> Self {
+        self.wrap_u = wrap_u;
+        self.wrap_v = wrap_v;
+        self
+    }
+
+    // Isotropic mipmapping picks a single mip level from the *larger* of
+    // `ddx`/`ddy`, which over-blurs a surface like a ground plane viewed at
+    // a grazing angle: one derivative axis is short (across the plane) but
+    // the other is long (receding into the distance), and picking the mip
+    // level for the long axis throws away detail the short axis could still
+    // resolve. Anisotropic sampling instead takes the mip level from the
+    // *shorter* axis and averages several samples spread along the longer
+    // one, so the short axis stays sharp while the long axis is still
+    // properly antialiased.
+    pub fn sample_anisotropic(&self, uv: glm::Vec2, ddx: glm::Vec2, ddy: glm::Vec2) -> [u8; 4] {
+        let texel_dx = glm::vec2(ddx.x * self.width as f32, ddx.y * self.height as f32);
+        let texel_dy = glm::vec2(ddy.x * self.width as f32, ddy.y * self.height as f32);
+        let len_dx = glm::length(&texel_dx);
+        let len_dy = glm::length(&texel_dy);
+
+        let (major_axis, major_len, minor_len) = if len_dx >= len_dy {
+            (ddx, len_dx, len_dy)
+        } else {
+            (ddy, len_dy, len_dx)
+        };
+
+        if self.max_anisotropy <= 1 || minor_len <= 0.0 || major_len <= minor_len {
+            return self.sample_with_lod(uv, self.mip_level_for_derivative(ddx, ddy));
+        }
+
+        let sample_count = (major_len / minor_len).round().clamp(1.0, self.max_anisotropy as f32) as u32;
+        let lod = minor_len.max(1.0).log2().max(0.0);
+
+        let mut sum = [0u32; 4];
+        for i in 0..sample_count {
+            // Centered offsets along the major axis, e.g. for 4 samples:
+            // -0.375, -0.125, 0.125, 0.375 (in units of the full derivative).
+            let t = (i as f32 + 0.5) / sample_count as f32 - 0.5;
+            let sample = self.sample_with_lod(uv + major_axis * t, lod);
+            for (channel, value) in sum.iter_mut().zip(sample) {
+                *channel += value as u32;
+            }
+        }
+        sum.map(|channel| (channel / sample_count) as u8)
+    }
+
+    fn sample_level(&self, width: u32, height: u32, pixels: &[u8], uv: glm::Vec2) -> [u8; 4] {
+        let uv = glm::vec2(self.wrap_u.apply(uv.x), self.wrap_v.apply(uv.y));
+        match self.filter {
+            FilterMode::Nearest => Self::sample_nearest(width, height, pixels, uv),
+            FilterMode::Bilinear => Self::sample_bilinear(width, height, pixels, uv)
+        }
+    }
+
+    // Nearest-neighbor lookup on an already-wrapped UV, with a final clamp
+    // guarding against the UV landing exactly on the texture's far edge.
+    fn sample_nearest(width: u32, height: u32, pixels: &[u8], uv: glm::Vec2) -> [u8; 4] {
+        let x = ((uv.x * width as f32) as i64).clamp(0, width as i64 - 1) as u32;
+        let y = ((uv.y * height as f32) as i64).clamp(0, height as i64 - 1) as u32;
+        Self::texel_in(width, pixels, x, y)
+    }
+
+    // Blends the four surrounding texels by the fractional part of the texel
+    // coordinate, interpolating each channel in u8-space and rounding.
+    fn sample_bilinear(width: u32, height: u32, pixels: &[u8], uv: glm::Vec2) -> [u8; 4] {
+        let fx = uv.x * width as f32 - 0.5;
+        let fy = uv.y * height as f32 - 0.5;
+        let x0 = fx.floor();
+        let y0 = fy.floor();
+        let tx = fx - x0;
+        let ty = fy - y0;
+
+        let clamp_x = |x: f32| (x as i64).clamp(0, width as i64 - 1) as u32;
+        let clamp_y = |y: f32| (y as i64).clamp(0, height as i64 - 1) as u32;
+
+        let c00 = Self::texel_in(width, pixels, clamp_x(x0), clamp_y(y0));
+        let c10 = Self::texel_in(width, pixels, clamp_x(x0 + 1.0), clamp_y(y0));
+        let c01 = Self::texel_in(width, pixels, clamp_x(x0), clamp_y(y0 + 1.0));
+        let c11 = Self::texel_in(width, pixels, clamp_x(x0 + 1.0), clamp_y(y0 + 1.0));
+
+        let mut out = [0u8; 4];
+        for i in 0..4 {
+            let top = c00[i] as f32 * (1.0 - tx) + c10[i] as f32 * tx;
+            let bottom = c01[i] as f32 * (1.0 - tx) + c11[i] as f32 * tx;
+            out[i] = (top * (1.0 - ty) + bottom * ty).round() as u8;
+        }
+        out
+    }
+}
+
+// A small bundle of named textures for pixel shaders that blend several
+// materials (albedo, normal, roughness, ...) instead of sampling just one.
+// The closure-based `PixelShader` can already capture multiple `&Texture`s
+// directly; this exists purely for ergonomics when there are enough of them
+// that naming each capture separately gets unwieldy.
+pub struct Sampler<'a> {
+    pub textures: Vec<&'a Texture>
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(textures: Vec<&'a Texture>) -> Self {
+        Sampler { textures }
+    }
+
+    pub fn sample(&self, index: usize, uv: glm::Vec2) -> [u8; 4] {
+        self.textures[index].sample(uv)
+    }
+}
+
+// Linearly blends two already-sampled colors by `mask` (0.0 keeps `a`, 1.0
+// keeps `b`), the common case for a UV-driven material mask.
+pub fn blend_colors(a: [u8; 4], b: [u8; 4], mask: f32) -> [u8; 4] {
+    let mask = mask.clamp(0.0, 1.0);
+    let mut out = [0u8; 4];
+    for i in 0..4 {
+        out[i] = (a[i] as f32 * (1.0 - mask) + b[i] as f32 * mask).round() as u8;
+    }
+    out
+}
+
+pub struct Camera {
+    pub view: glm::Mat4,
+    pub projection: glm::Mat4,
+    // Remembered so `set_aspect` can rebuild `projection` on resize without
+    // the caller having to keep `fovy`/`near`/`far` around itself. `None`
+    // for an orthographic camera, whose bounds don't factor into a single
+    // aspect ratio the same way.
+    perspective_params: Option<(f32, f32, f32)>
+}
+
+impl Camera {
+    pub fn new(aspect: f32, fovy: f32, near: f32, far: f32) -> Self {
+        Camera {
+            view: glm::identity(),
+            projection: glm::perspective(aspect, fovy, near, far),
+            perspective_params: Some((fovy, near, far))
+        }
+    }
+
+    /// Builds a camera with an orthographic projection instead, which drops
+    /// perspective foreshortening entirely. Useful for 2D/UI rendering and
+    /// for comparing a scene's depth against its perspective rendering.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        Camera {
+            view: glm::identity(),
+            projection: glm::ortho(left, right, bottom, top, near, far),
+            perspective_params: None
+        }
+    }
+
+    pub fn look_at(&mut self, eye: glm::Vec3, target: glm::Vec3, up: glm::Vec3) {
+        self.view = glm::look_at(&eye, &target, &up);
+    }
+
+    /// Rebuilds `projection` for a new aspect ratio, e.g. after a window
+    /// resize. A no-op on a camera built via `orthographic`, whose bounds
+    /// aren't parameterized by a single aspect ratio.
+    pub fn set_aspect(&mut self, aspect: f32) {
+        if let Some((fovy, near, far)) = self.perspective_params {
+            self.projection = glm::perspective(aspect, fovy, near, far);
+        }
+    }
+
+    /// `projection * view`, the multiply order this crate uses everywhere
+    /// else — keeping it here means callers can't get it backwards.
+    pub fn view_projection(&self) -> glm::Mat4 {
+        self.projection * self.view
+    }
+
+    /// `projection * view * model`, i.e. `view_projection() * model`.
+    pub fn mvp(&self, model: &glm::Mat4) -> glm::Mat4 {
+        self.view_projection() * model
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BoundingSphere {
+    pub center: glm::Vec3,
+    pub radius: f32
+}
+
+// A plane stored as (normal, distance) such that a point `p` is on the
+// side the normal points to when `dot(normal, p) + distance >= 0`.
+#[derive(Clone, Copy, Debug)]
+struct FrustumPlane {
+    normal: glm::Vec3,
+    distance: f32
+}
+
+impl FrustumPlane {
+    fn from_coefficients(x: f32, y: f32, z: f32, w: f32) -> Self {
+        let normal = glm::vec3(x, y, z);
+        let length = glm::length(&normal);
+        FrustumPlane { normal: normal / length, distance: w / length }
+    }
+
+    fn distance_to(&self, point: &glm::Vec3) -> f32 {
+        glm::dot(&self.normal, point) + self.distance
+    }
+}
+
+/// The six half-space planes (left, right, bottom, top, near, far) bounding
+/// a camera's view volume, extracted from its combined view-projection
+/// matrix via the Gribb-Hartmann method. Lets whole meshes be rejected
+/// before any per-triangle work is done.
+pub struct Frustum {
+    planes: [FrustumPlane; 6]
+}
+
+impl Frustum {
+    pub fn from_view_projection(view_projection: &glm::Mat4) -> Self {
+        let m = view_projection;
+        let row = |i: usize| (m[(i, 0)], m[(i, 1)], m[(i, 2)], m[(i, 3)]);
+        let (r0x, r0y, r0z, r0w) = row(0);
+        let (r1x, r1y, r1z, r1w) = row(1);
+        let (r2x, r2y, r2z, r2w) = row(2);
+        let (r3x, r3y, r3z, r3w) = row(3);
+        Frustum {
+            planes: [
+                FrustumPlane::from_coefficients(r3x + r0x, r3y + r0y, r3z + r0z, r3w + r0w),
+                FrustumPlane::from_coefficients(r3x - r0x, r3y - r0y, r3z - r0z, r3w - r0w),
+                FrustumPlane::from_coefficients(r3x + r1x, r3y + r1y, r3z + r1z, r3w + r1w),
+                FrustumPlane::from_coefficients(r3x - r1x, r3y - r1y, r3z - r1z, r3w - r1w),
+                FrustumPlane::from_coefficients(r3x + r2x, r3y + r2y, r3z + r2z, r3w + r2w),
+                FrustumPlane::from_coefficients(r3x - r2x, r3y - r2y, r3z - r2z, r3w - r2w)
+            ]
+        }
+    }
+
+    // A sphere is fully outside the frustum as soon as it's entirely on the
+    // negative side of any single plane; otherwise it's at least partially
+    // visible.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| plane.distance_to(&sphere.center) >= -sphere.radius)
+    }
+}
+
+/// Tracks an eye position plus a yaw/pitch orientation and turns WASD/mouse
+/// input into movement, independently of SDL so it can be unit tested. Yaw
+/// 0 faces down -Z and pitch is clamped to avoid flipping past straight up
+/// or down.
+pub struct CameraController {
+    pub position: glm::Vec3,
+    yaw: f32,
+    pitch: f32
+}
+
+impl CameraController {
+    pub fn new(position: glm::Vec3, yaw: f32, pitch: f32) -> Self {
+        CameraController { position, yaw, pitch }
+    }
+
+    pub fn forward(&self) -> glm::Vec3 {
+        glm::vec3(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            -self.pitch.cos() * self.yaw.cos()
+        )
+    }
+
+    fn right(&self) -> glm::Vec3 {
+        glm::normalize(&glm::cross(&self.forward(), &glm::vec3(0.0, 1.0, 0.0)))
+    }
+
+    pub fn process_keyboard(&mut self, forward: bool, backward: bool, left: bool, right: bool, speed: f32) {
+        let forward_vector = self.forward();
+        let right_vector = self.right();
+        if forward { self.position += forward_vector * speed; }
+        if backward { self.position -= forward_vector * speed; }
+        if right { self.position += right_vector * speed; }
+        if left { self.position -= right_vector * speed; }
+    }
+
+    pub fn process_mouse(&mut self, dx: f32, dy: f32, sensitivity: f32) {
+        self.yaw += dx * sensitivity;
+        self.pitch = (self.pitch - dy * sensitivity)
+            .clamp(-std::f32::consts::FRAC_PI_2 + 0.01, std::f32::consts::FRAC_PI_2 - 0.01);
+    }
+}
+
+// A friendlier alternative to `CameraController`'s free-fly for inspecting a
+// single object: orbits `target` at `distance`, driven by mouse drag
+// (azimuth/elevation) and scroll-wheel zoom instead of WASD.
+pub struct OrbitCamera {
+    pub target: glm::Vec3,
+    pub distance: f32,
+    yaw: f32,
+    pitch: f32
+}
+
+impl OrbitCamera {
+    pub fn new(target: glm::Vec3, distance: f32, yaw: f32, pitch: f32) -> Self {
+        OrbitCamera { target, distance, yaw, pitch }
+    }
+
+    // Frames the AABB spanning `min`/`max` (see `compute_aabb`) entirely
+    // within a `fovy`-radian vertical field of view: the target is the
+    // AABB's center, and the distance is derived from its bounding sphere's
+    // radius (half the AABB's diagonal) so that sphere exactly touches the
+    // frustum's sides at `fovy / 2`. Starts looking straight down the AABB's
+    // own +Z axis (`yaw`/`pitch` both 0).
+    pub fn framing(min: glm::Vec3, max: glm::Vec3, fovy: f32) -> Self {
+        let center = (min + max) * 0.5;
+        let radius = glm::length(&(max - min)) * 0.5;
+        let distance = radius / (fovy * 0.5).sin();
+        OrbitCamera::new(center, distance, 0.0, 0.0)
+    }
+
+    pub fn eye(&self) -> glm::Vec3 {
+        self.target + glm::vec3(
+            self.distance * self.pitch.cos() * self.yaw.sin(),
+            self.distance * self.pitch.sin(),
+            self.distance * self.pitch.cos() * self.yaw.cos()
+        )
+    }
+
+    pub fn process_mouse(&mut self, dx: f32, dy: f32, sensitivity: f32) {
+        self.yaw += dx * sensitivity;
+        self.pitch = (self.pitch + dy * sensitivity)
+            .clamp(-std::f32::consts::FRAC_PI_2 + 0.01, std::f32::consts::FRAC_PI_2 - 0.01);
+    }
+
+    pub fn process_scroll(&mut self, delta: f32, sensitivity: f32, min_distance: f32) {
+        self.distance = (self.distance - delta * sensitivity).max(min_distance);
+    }
+
+    pub fn view_matrix(&self) -> glm::Mat4 {
+        glm::look_at(&self.eye(), &self.target, &glm::vec3(0.0, 1.0, 0.0))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Vertex {
+    pub position: glm::Vec3,
+    pub uv: glm::Vec2,
+    pub normal: glm::Vec3
+}
+
+impl Add<Vertex> for Vertex {
+    type Output = Vertex;
+    fn add(self, rhs: Vertex) -> Self::Output {
+        Vertex {
+            position: self.position + rhs.position,
+            uv: self.uv + rhs.uv,
+            normal: self.normal + rhs.normal
+        }
+    }
+}
+
+impl Sub<Vertex> for Vertex {
+    type Output = Vertex;
+    fn sub(self, rhs: Vertex) -> Self::Output {
+        Vertex {
+            position: self.position - rhs.position,
+            uv: self.uv - rhs.uv,
+            normal: self.normal - rhs.normal
+        }
+    }
+}
+
+impl Mul<f32> for Vertex {
+    type Output = Vertex;
+    fn mul(self, rhs: f32) -> Self::Output {
+        Vertex {
+            position: self.position * rhs,
+            uv: self.uv * rhs,
+            normal: self.normal * rhs
+        }
+    }
+}
+
+pub trait Linear: Copy + Add<Self, Output=Self> + Sub<Self, Output=Self> + Mul<f32, Output=Self> {
+    // World/view-space position this attribute carries, i.e. whatever the
+    // vertex shader wrote into `position` before returning a clip-space
+    // coordinate. Used by `RenderContext::with_clip_plane`'s per-fragment
+    // test; every `Linear` type in this crate already has such a field.
+    fn position(&self) -> glm::Vec3;
+}
+
+impl Linear for Vertex {
+    fn position(&self) -> glm::Vec3 {
+        self.position
+    }
+}
+
+// Lets `draw_indexed_instanced` apply a different model matrix per instance
+// without knowing a vertex type's field layout: `position` is transformed
+// as a point, `normal` as a direction under the matrix's linear part.
+pub trait Transform: Copy {
+    fn transformed(&self, model: &glm::Mat4) -> Self;
+}
+
+impl Transform for Vertex {
+    fn transformed(&self, model: &glm::Mat4) -> Self {
+        let position = model * glm::vec4(self.position.x, self.position.y, self.position.z, 1.0);
+        let normal = glm::mat4_to_mat3(model) * self.normal;
+        Vertex { position: position.xyz(), uv: self.uv, normal }
+    }
+}
+
+/// Vertex attribute used by the Gouraud shading example: lighting is
+/// resolved once per vertex into `color`, and the rasterizer interpolates
+/// the already-shaded color rather than the normal, which is what produces
+/// the faceted banding on low-poly meshes.
+#[derive(Clone, Copy)]
+pub struct GouraudVertex {
+    pub position: glm::Vec3,
+    pub color: glm::Vec3
+}
+
+impl Add<GouraudVertex> for GouraudVertex {
+    type Output = GouraudVertex;
+    fn add(self, rhs: GouraudVertex) -> Self::Output {
+        GouraudVertex {
+            position: self.position + rhs.position,
+            color: self.color + rhs.color
+        }
+    }
+}
+
+impl Sub<GouraudVertex> for GouraudVertex {
+    type Output = GouraudVertex;
+    fn sub(self, rhs: GouraudVertex) -> Self::Output {
+        GouraudVertex {
+            position: self.position - rhs.position,
+            color: self.color - rhs.color
+        }
+    }
+}
+
+impl Mul<f32> for GouraudVertex {
+    type Output = GouraudVertex;
+    fn mul(self, rhs: f32) -> Self::Output {
+        GouraudVertex {
+            position: self.position * rhs,
+            color: self.color * rhs
+        }
+    }
+}
+
+impl Linear for GouraudVertex {
+    fn position(&self) -> glm::Vec3 {
+        self.position
+    }
+}
+
+/// Vertex attribute used for tangent-space normal mapping: like `Vertex`,
+/// plus a per-vertex `tangent` so a pixel shader can build a TBN basis and
+/// perturb the interpolated normal with a sampled normal map.
+#[derive(Clone, Copy, Debug)]
+pub struct TangentVertex {
+    pub position: glm::Vec3,
+    pub uv: glm::Vec2,
+    pub normal: glm::Vec3,
+    pub tangent: glm::Vec3
+}
+
+impl Add<TangentVertex> for TangentVertex {
+    type Output = TangentVertex;
+    fn add(self, rhs: TangentVertex) -> Self::Output {
+        TangentVertex {
+            position: self.position + rhs.position,
+            uv: self.uv + rhs.uv,
+            normal: self.normal + rhs.normal,
+            tangent: self.tangent + rhs.tangent
+        }
+    }
+}
+
+impl Sub<TangentVertex> for TangentVertex {
+    type Output = TangentVertex;
+    fn sub(self, rhs: TangentVertex) -> Self::Output {
+        TangentVertex {
+            position: self.position - rhs.position,
+            uv: self.uv - rhs.uv,
+            normal: self.normal - rhs.normal,
+            tangent: self.tangent - rhs.tangent
+        }
+    }
+}
+
+impl Mul<f32> for TangentVertex {
+    type Output = TangentVertex;
+    fn mul(self, rhs: f32) -> Self::Output {
+        TangentVertex {
+            position: self.position * rhs,
+            uv: self.uv * rhs,
+            normal: self.normal * rhs,
+            tangent: self.tangent * rhs
+        }
+    }
+}
+
+impl Linear for TangentVertex {
+    fn position(&self) -> glm::Vec3 {
+        self.position
+    }
+}
+
+// Vertex attribute carrying both a `uv` and a per-vertex `color`, for a
+// pixel shader that samples a texture and tints it by the interpolated
+// color instead of using either attribute alone.
+#[derive(Clone, Copy)]
+pub struct ColoredTexturedVertex {
+    pub position: glm::Vec3,
+    pub uv: glm::Vec2,
+    pub color: glm::Vec3
+}
+
+impl Add<ColoredTexturedVertex> for ColoredTexturedVertex {
+    type Output = ColoredTexturedVertex;
+    fn add(self, rhs: ColoredTexturedVertex) -> Self::Output {
+        ColoredTexturedVertex {
+            position: self.position + rhs.position,
+            uv: self.uv + rhs.uv,
+            color: self.color + rhs.color
+        }
+    }
+}
+
+impl Sub<ColoredTexturedVertex> for ColoredTexturedVertex {
+    type Output = ColoredTexturedVertex;
+    fn sub(self, rhs: ColoredTexturedVertex) -> Self::Output {
+        ColoredTexturedVertex {
+            position: self.position - rhs.position,
+            uv: self.uv - rhs.uv,
+            color: self.color - rhs.color
+        }
+    }
+}
+
+impl Mul<f32> for ColoredTexturedVertex {
+    type Output = ColoredTexturedVertex;
+    fn mul(self, rhs: f32) -> Self::Output {
+        ColoredTexturedVertex {
+            position: self.position * rhs,
+            uv: self.uv * rhs,
+            color: self.color * rhs
+        }
+    }
+}
+
+impl Linear for ColoredTexturedVertex {
+    fn position(&self) -> glm::Vec3 {
+        self.position
+    }
+}
+
+// Accumulates a per-face tangent (pointing along increasing U) onto each of
+// a face's three vertices, weighted by nothing more than face count, then
+// orthogonalizes and normalizes the result per vertex (Gram-Schmidt against
+// the vertex normal) so shared vertices end up with a reasonable averaged
+// tangent instead of an arbitrary one from whichever face touched them last.
+pub fn compute_tangents(vertices: &[Vertex], indices: &[usize]) -> Vec<glm::Vec3> {
+    let mut accumulated = vec![glm::vec3(0.0, 0.0, 0.0); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let (i0, i1, i2) = (face[0], face[1], face[2]);
+        let (v0, v1, v2) = (vertices[i0], vertices[i1], vertices[i2]);
+
+        let edge1 = v1.position - v0.position;
+        let edge2 = v2.position - v0.position;
+        let delta_uv1 = v1.uv - v0.uv;
+        let delta_uv2 = v2.uv - v0.uv;
+
+        let denom = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+        if denom.abs() < 1e-8 {
+            continue;
+        }
+        let r = 1.0 / denom;
+        let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) * r;
+
+        for &i in &[i0, i1, i2] {
+            accumulated[i] += tangent;
+        }
+    }
+
+    vertices.iter().zip(accumulated).map(|(v, tangent)| {
+        let tangent = tangent - v.normal * glm::dot(&v.normal, &tangent);
+        if glm::length(&tangent) < 1e-8 {
+            // Degenerate (e.g. an isolated vertex with no faces): fall back
+            // to any axis orthogonal to the normal rather than dividing by
+            // a near-zero length.
+            let fallback = if v.normal.x.abs() < 0.9 { glm::vec3(1.0, 0.0, 0.0) } else { glm::vec3(0.0, 1.0, 0.0) };
+            return glm::normalize(&(fallback - v.normal * glm::dot(&v.normal, &fallback)));
+        }
+        glm::normalize(&tangent)
+    }).collect()
+}
+
+// A face's geometric normal duplicated onto each of its three vertices, so
+// adjacent faces don't share a vertex's normal (each keeps its own flat
+// shading). Unlike `compute_smooth_normals`, this can't write in place
+// since sharing a position across faces now means duplicating the vertex,
+// so it returns a fresh vertex/index buffer instead of mutating `vertices`.
+// The motivating case is an OBJ with only positions: run this (or
+// `compute_smooth_normals`) over the parsed mesh before lighting it.
+pub fn compute_flat_normals(vertices: &[Vertex], indices: &[usize]) -> (Vec<Vertex>, Vec<usize>) {
+    let mut out_vertices = Vec::with_capacity(indices.len());
+    let mut out_indices = Vec::with_capacity(indices.len());
+
+    for face in indices.chunks_exact(3) {
+        let (v0, v1, v2) = (vertices[face[0]], vertices[face[1]], vertices[face[2]]);
+        let normal = glm::normalize(&glm::cross(&(v1.position - v0.position), &(v2.position - v0.position)));
+
+        for v in [v0, v1, v2] {
+            out_indices.push(out_vertices.len());
+            out_vertices.push(Vertex { normal, ..v });
+        }
+    }
+
+    (out_vertices, out_indices)
+}
+
+// Averages each face's geometric normal into every vertex position it
+// touches, weighted by nothing more than face count (the same scheme
+// `compute_tangents` uses), giving smooth per-vertex normals without
+// duplicating any vertex. A position with no incident face keeps its
+// existing normal rather than being zeroed out.
+pub fn compute_smooth_normals(vertices: &mut [Vertex], indices: &[usize]) {
+    let mut accumulated = vec![glm::vec3(0.0, 0.0, 0.0); vertices.len()];
+
+    for face in indices.chunks_exact(3) {
+        let (i0, i1, i2) = (face[0], face[1], face[2]);
+        let (v0, v1, v2) = (vertices[i0], vertices[i1], vertices[i2]);
+        let normal = glm::cross(&(v1.position - v0.position), &(v2.position - v0.position));
+        for &i in &[i0, i1, i2] {
+            accumulated[i] += normal;
+        }
+    }
+
+    for (vertex, normal) in vertices.iter_mut().zip(accumulated) {
+        if glm::length(&normal) > 1e-8 {
+            vertex.normal = glm::normalize(&normal);
+        }
+    }
+}
+
+// Returns the axis-aligned min/max corners enclosing every vertex position,
+// for auto-framing a freshly loaded model. Panics if `vertices` is empty,
+// same as there being no sensible AABB to return.
+pub fn compute_aabb(vertices: &[Vertex]) -> (glm::Vec3, glm::Vec3) {
+    let mut min = vertices[0].position;
+    let mut max = vertices[0].position;
+    for vertex in &vertices[1..] {
+        min = glm::vec3(min.x.min(vertex.position.x), min.y.min(vertex.position.y), min.z.min(vertex.position.z));
+        max = glm::vec3(max.x.max(vertex.position.x), max.y.max(vertex.position.y), max.z.max(vertex.position.z));
+    }
+    (min, max)
+}
+
+// Per-fragment Blinn-Phong specular term: the half-vector between the view
+// and light directions stands in for the reflection vector (cheaper than
+// computing an actual reflection and close enough for common light/view
+// angles), raised to `shininess` so the highlight peaks sharply when the
+// half-vector lines up with the normal. `normal`, `view_dir` and `light_dir`
+// are all expected to already be unit length.
+pub fn blinn_phong_specular(normal: glm::Vec3, view_dir: glm::Vec3, light_dir: glm::Vec3, shininess: f32) -> f32 {
+    let half_vector = glm::normalize(&(view_dir + light_dir));
+    f32::max(glm::dot(&normal, &half_vector), 0.0).powf(shininess)
+}
+
+// How silhouette edges are smoothed. `Coverage` is a cheap, scanline-local
+// approximation that only softens a triangle's outer edges (shared interior
+// edges between triangles are unaffected). `Supersample(factor)` is purely
+// informational here: the caller renders into a `factor`x larger `TextureBuffer`
+// and downsamples with `TextureBuffer::resolve`, same as the demo in `main`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AntiAlias {
+    None,
+    Coverage,
+    Supersample(u32)
+}
+
+// How a fragment's (non-antialiased) coverage is decided. `TopLeftRule`
+// (the default) gives every pixel exactly one owner among triangles sharing
+// an edge. `BarycentricInside` instead accepts any pixel whose barycentric
+// weights are all non-negative, which is simpler and what most textbook
+// rasterizers use, but ties on a shared edge aren't broken: two triangles
+// sharing that edge can both (or neither) draw a boundary pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum CoverageTest {
+    #[default]
+    TopLeftRule,
+    BarycentricInside
+}
+
+// How a triangle's color varies across its surface. `Smooth` (the default)
+// interpolates each pixel's vertex attributes via barycentric weights before
+// shading. `Flat` shades once per triangle using `v0` as the provoking
+// vertex and reuses that color for every covered pixel, giving a faceted
+// look (or a debugging aid) and skipping per-pixel interpolation entirely.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ShadeModel {
+    #[default]
+    Smooth,
+    Flat
+}
+
+// Replaces the user's pixel shader with a debugging visualization built from
+// per-fragment plumbing that would otherwise just feed the shader (depth,
+// barycentric weights), for inspecting rasterizer behavior independent of
+// whatever the scene's own shading looks like.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum DebugOutput {
+    #[default]
+    Normal,
+    // Grayscale visualization of depth, linearized from NDC z against
+    // `near`/`far`: a fragment at `near` is white, at `far` is black.
+    Depth { near: f32, far: f32 },
+    // Visualizes each fragment's barycentric weights directly as RGB.
+    Barycentric,
+    // Runs the pixel shader as normal, but also increments an attached
+    // `OverdrawBuffer`'s per-pixel counter for every fragment that survives
+    // rasterization; see `with_overdraw_tracking`. Unlike `Depth` and
+    // `Barycentric`, this doesn't touch the color output itself — call
+    // `OverdrawBuffer::resolve_heatmap` separately to visualize the counts.
+    Overdraw
+}
+
+// A sub-rectangle of a `TextureBuffer`, in target pixels, that NDC coordinates
+// are mapped into instead of the full buffer. Lets several cameras share one
+// buffer, e.g. split-screen or picture-in-picture.
+#[derive(Copy, Clone)]
+pub struct Viewport {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
+
+// Where within a pixel's unit square its sample point sits, e.g. for
+// deciding coverage or interpolating attributes. Renderers disagree on this:
+// `TOP_LEFT` treats pixel `(x, y)`'s sample point as its integer origin
+// (this rasterizer's original, hardcoded behavior), while `PIXEL_CENTER`
+// follows OpenGL/D3D10+ and samples at `(x + 0.5, y + 0.5)`. Comparing
+// against a golden image from another renderer that assumes the other
+// convention shows up as a consistent half-pixel offset; picking the
+// matching `SampleConvention` fixes it without touching the geometry.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampleConvention {
+    pub pixel_center_offset: f32
+}
+
+impl SampleConvention {
+    pub const TOP_LEFT: SampleConvention = SampleConvention { pixel_center_offset: 0.0 };
+    pub const PIXEL_CENTER: SampleConvention = SampleConvention { pixel_center_offset: 0.5 };
+}
+
+impl Default for SampleConvention {
+    fn default() -> Self {
+        Self::TOP_LEFT
+    }
+}
+
+// Abstracts over vertex shaders that always produce a position and ones that
+// may discard a vertex, so `RenderContext` can stay generic over either
+// without forcing the common case to wrap every return value in `Some`.
+pub trait VertexShader<V> {
+    fn shade(&self, vertex: &mut V) -> Option<glm::Vec4>;
+}
+
+impl<V, F: Fn(&mut V) -> glm::Vec4> VertexShader<V> for F {
+    fn shade(&self, vertex: &mut V) -> Option<glm::Vec4> {
+        Some(self(vertex))
+    }
+}
+
+// Wraps a shader that signals discard directly, for use with `new_with_cull`.
+// A plain `F: Fn(&mut V) -> glm::Vec4` already implements `VertexShader`
+// above, so this wrapper only exists for the `Option`-returning case.
+pub struct Discard<F>(pub F);
+
+impl<V, F: Fn(&mut V) -> Option<glm::Vec4>> VertexShader<V> for Discard<F> {
+    fn shade(&self, vertex: &mut V) -> Option<glm::Vec4> {
+        (self.0)(vertex)
+    }
+}
+
+// Abstracts over pixel shaders that always produce a color and ones that may
+// discard a fragment, mirroring `VertexShader` above.
+pub trait PixelShader<V> {
+    fn shade(&self, vertex: &V) -> Option<[u8; 4]>;
+}
+
+impl<V, F: Fn(&V) -> [u8; 4]> PixelShader<V> for F {
+    fn shade(&self, vertex: &V) -> Option<[u8; 4]> {
+        Some(self(vertex))
+    }
+}
+
+// Wraps a shader that signals discard directly, for use with
+// `new_with_pixel_discard`.
+pub struct DiscardPixel<F>(pub F);
+
+impl<V, F: Fn(&V) -> Option<[u8; 4]>> PixelShader<V> for DiscardPixel<F> {
+    fn shade(&self, vertex: &V) -> Option<[u8; 4]> {
+        (self.0)(vertex)
+    }
+}
+
+// Which screen-space winding a triangle's vertices must follow to be
+// considered front-facing. Affects how `CullMode` is interpreted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrontFace {
+    Clockwise,
+    CounterClockwise
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CullMode {
+    None,
+    Front,
+    Back
+}
+
+// How `draw_indexed_triangles` interprets its index buffer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PrimitiveTopology {
+    TriangleList,
+    TriangleStrip,
+    TriangleFan
+}
+
+// How `draw_indexed_lines` interprets its index buffer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineTopology {
+    LineList,
+    LineStrip
+}
+
+// Bright magenta rarely occurs in lit scenes, so `debug_clip` overlay edges
+// stay legible against arbitrary shaded content underneath them.
+const DEBUG_CLIP_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+// Wraps a persistent `rayon::ThreadPool` so the per-row/per-tile parallelism
+// already used inside `RenderContext`'s raster backends can be reused across
+// many draw calls instead of spinning up rayon's global pool's worker
+// threads fresh each time — worthwhile when a frame issues many small draw
+// calls. Deliberately doesn't wrap `RenderContext` itself: its vertex/pixel
+// shader type parameters aren't guaranteed `Send`, so instead callers wrap
+// their own draw calls with `install`.
+pub struct Renderer {
+    pool: rayon::ThreadPool
+}
+
+impl Renderer {
+    // `num_threads` follows `rayon::ThreadPoolBuilder`'s own convention: 0
+    // means "use available parallelism".
+    pub fn new(num_threads: usize) -> Self {
+        Renderer {
+            pool: rayon::ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap()
+        }
+    }
+
+    pub fn with_available_parallelism() -> Self {
+        Self::new(0)
+    }
+
+    // Runs `f` on this renderer's thread pool instead of rayon's global one,
+    // so every `into_par_iter()` call inside `f` (e.g. a `RenderContext` draw
+    // call) reuses the same worker threads the next time `install` is called.
+    pub fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        self.pool.install(f)
+    }
+}
+
+// A snapshot of `RenderContext`'s per-frame counters, for diagnosing
+// whether a scene is slow because of triangle count (`triangles_submitted`,
+// `triangles_culled`, `triangles_clipped`, `triangles_offscreen`) or overdraw
+// (`fragments_shaded`, `fragments_depth_rejected`).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    pub triangles_submitted: u64,
+    pub triangles_culled: u64,
+    pub triangles_clipped: u64,
+    pub triangles_offscreen: u64,
+    pub triangles_occlusion_culled: u64,
+    pub fragments_shaded: u64,
+    pub fragments_depth_rejected: u64
+}
+
+// Backs `RenderStats` with atomics rather than plain `u64`s, since
+// `draw_flat_triangle_common` increments the fragment counters from
+// multiple rayon row workers concurrently.
+struct AtomicRenderStats {
+    triangles_submitted: AtomicU64,
+    triangles_culled: AtomicU64,
+    triangles_clipped: AtomicU64,
+    triangles_offscreen: AtomicU64,
+    triangles_occlusion_culled: AtomicU64,
+    fragments_shaded: AtomicU64,
+    fragments_depth_rejected: AtomicU64
+}
+
+impl AtomicRenderStats {
+    fn new() -> Self {
+        AtomicRenderStats {
+            triangles_submitted: AtomicU64::new(0),
+            triangles_culled: AtomicU64::new(0),
+            triangles_clipped: AtomicU64::new(0),
+            triangles_offscreen: AtomicU64::new(0),
+            triangles_occlusion_culled: AtomicU64::new(0),
+            fragments_shaded: AtomicU64::new(0),
+            fragments_depth_rejected: AtomicU64::new(0)
+        }
+    }
+
+    fn reset(&self) {
+        self.triangles_submitted.store(0, Ordering::Relaxed);
+        self.triangles_culled.store(0, Ordering::Relaxed);
+        self.triangles_clipped.store(0, Ordering::Relaxed);
+        self.triangles_offscreen.store(0, Ordering::Relaxed);
+        self.triangles_occlusion_culled.store(0, Ordering::Relaxed);
+        self.fragments_shaded.store(0, Ordering::Relaxed);
+        self.fragments_depth_rejected.store(0, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> RenderStats {
+        RenderStats {
+            triangles_submitted: self.triangles_submitted.load(Ordering::Relaxed),
+            triangles_culled: self.triangles_culled.load(Ordering::Relaxed),
+            triangles_clipped: self.triangles_clipped.load(Ordering::Relaxed),
+            triangles_offscreen: self.triangles_offscreen.load(Ordering::Relaxed),
+            triangles_occlusion_culled: self.triangles_occlusion_culled.load(Ordering::Relaxed),
+            fragments_shaded: self.fragments_shaded.load(Ordering::Relaxed),
+            fragments_depth_rejected: self.fragments_depth_rejected.load(Ordering::Relaxed)
+        }
+    }
+}
+
+// A scratch geometry buffer for `RenderContext::draw_with`: `triangle` and
+// `vertex` let a caller emit procedural geometry one piece at a time instead
+// of building a full index/vertex array up front, while the sink itself
+// still batches everything into the same indexed representation the rest of
+// the crate draws from.
+pub struct TriangleSink<V> {
+    vertices: Vec<V>,
+    indices: Vec<usize>
+}
+
+impl<V> TriangleSink<V> {
+    fn new() -> Self {
+        TriangleSink { vertices: Vec::new(), indices: Vec::new() }
+    }
+
+    // Appends `v` and returns its index, for wiring up shared vertices (fans,
+    // strips) by hand alongside `triangle`.
+    pub fn vertex(&mut self, v: V) -> usize {
+        self.vertices.push(v);
+        self.vertices.len() - 1
+    }
+
+    // Appends three fresh, auto-indexed vertices as one triangle.
+    pub fn triangle(&mut self, a: V, b: V, c: V) {
+        let i0 = self.vertex(a);
+        let i1 = self.vertex(b);
+        let i2 = self.vertex(c);
+        self.indices.extend_from_slice(&[i0, i1, i2]);
+    }
+}
+
+pub struct RenderContext<'a, 'b, V: Clone + Linear + Sync,
+    VS: VertexShader<V>,
+    PS: PixelShader<V> + Sync> {
+    cull_mode: CullMode,
+    cull_method: CullMethod,
+    front_face: FrontFace,
+    topology: PrimitiveTopology,
+    restart_index: Option<usize>,
+    target: &'a mut TextureBuffer<'b>,
+    vertex_shader: VS,
+    pixel_shader: PS,
+    depth: Option<(&'a mut DepthBuffer, DepthFunc)>,
+    stencil: Option<(&'a mut StencilBuffer, StencilFunc, u8, StencilOp)>,
+    overdraw: Option<&'a mut OverdrawBuffer>,
+    id_buffer: Option<(&'a mut IdBuffer, u32)>,
+    fog: Option<Fog>,
+    blend: BlendMode,
+    backend: RasterBackend,
+    sort_transparent: bool,
+    scissor: Option<(u32, u32, u32, u32)>,
+    viewport: Viewport,
+    anti_alias: AntiAlias,
+    output_srgb: bool,
+    color_write: bool,
+    depth_range: DepthRange,
+    debug_clip: bool,
+    max_triangle_area: Option<f32>,
+    sample_convention: SampleConvention,
+    coverage_test: CoverageTest,
+    shade_model: ShadeModel,
+    debug_output: DebugOutput,
+    conservative: bool,
+    hierarchical_z: Option<&'a HierarchicalDepthBuffer>,
+    clip_plane: Option<glm::Vec4>,
+    stats: AtomicRenderStats,
+    phantom: PhantomData<V>,
+    // Reused across `draw_indexed_triangles` calls instead of being
+    // reallocated every frame/instance; cleared and refilled each call.
+    scratch_vertices: Vec<V>,
+    scratch_positions: Vec<Option<glm::Vec4>>
+}
+
+impl<'a, 'b, V: Clone + Linear + Sync, VS: Fn(&mut V) -> glm::Vec4,
+    PS: Fn(&V) -> [u8; 4] + Sync> RenderContext<'a, 'b, V, VS, PS> {
+    // The common case: every vertex produces a position and every pixel
+    // produces a color.
+    pub fn new(cull_mode: CullMode, target: &'a mut TextureBuffer<'b>, vertex_shader: VS, pixel_shader: PS) -> Self {
+        Self::new_with_shader(cull_mode, target, vertex_shader, pixel_shader)
+    }
+}
+
+impl<'a, 'b, V: Clone + Linear + Sync, VS: Fn(&mut V) -> Option<glm::Vec4>,
+    PS: Fn(&V) -> [u8; 4] + Sync> RenderContext<'a, 'b, V, Discard<VS>, PS> {
+    // Like `new`, but the vertex shader may return `None` to discard a
+    // vertex, skipping every triangle that references it. Useful for cheap
+    // level-of-detail culling or cutaway effects.
+    pub fn new_with_cull(cull_mode: CullMode, target: &'a mut TextureBuffer<'b>, vertex_shader: VS, pixel_shader: PS) -> Self {
+        Self::new_with_shader(cull_mode, target, Discard(vertex_shader), pixel_shader)
+    }
+}
+
+impl<'a, 'b, V: Clone + Linear + Sync, VS: Fn(&mut V) -> glm::Vec4,
+    PS: Fn(&V) -> Option<[u8; 4]> + Sync> RenderContext<'a, 'b, V, VS, DiscardPixel<PS>> {
+    // Like `new`, but the pixel shader may return `None` to leave a fragment
+    // unwritten (and its depth sample untouched), e.g. for alpha-tested
+    // cutouts such as foliage or fences.
+    pub fn new_with_pixel_discard(cull_mode: CullMode, target: &'a mut TextureBuffer<'b>, vertex_shader: VS, pixel_shader: PS) -> Self {
+        Self::new_with_shader(cull_mode, target, vertex_shader, DiscardPixel(pixel_shader))
+    }
+}
+
+impl<'a, 'b, V: Clone + Linear + Sync,
+    VS: VertexShader<V>,
+    PS: PixelShader<V> + Sync> RenderContext<'a, 'b, V, VS, PS> {
+    fn new_with_shader(cull_mode: CullMode, target: &'a mut TextureBuffer<'b>, vertex_shader: VS, pixel_shader: PS) -> Self {
+        let viewport = Viewport { x: 0, y: 0, width: target.size.0, height: target.size.1 };
+        RenderContext {
+            cull_mode,
+            cull_method: CullMethod::ScreenSpace,
+            front_face: FrontFace::CounterClockwise,
+            topology: PrimitiveTopology::TriangleList,
+            restart_index: None,
+            target,
+            vertex_shader,
+            pixel_shader,
+            depth: None,
+            stencil: None,
+            overdraw: None,
+            id_buffer: None,
+            fog: None,
+            blend: BlendMode::Opaque,
+            backend: RasterBackend::Scanline,
+            sort_transparent: false,
+            scissor: None,
+            viewport,
+            anti_alias: AntiAlias::None,
+            output_srgb: false,
+            color_write: true,
+            depth_range: DepthRange::NegativeOneToOne,
+            debug_clip: false,
+            max_triangle_area: None,
+            sample_convention: SampleConvention::default(),
+            coverage_test: CoverageTest::default(),
+            shade_model: ShadeModel::default(),
+            debug_output: DebugOutput::default(),
+            conservative: false,
+            hierarchical_z: None,
+            clip_plane: None,
+            stats: AtomicRenderStats::new(),
+            phantom: PhantomData,
+            scratch_vertices: Vec::new(),
+            scratch_positions: Vec::new()
+        }
+    }
+
+    // Which screen-space winding counts as front-facing for `CullMode`.
+    // Defaults to `CounterClockwise`, matching the sign of the winding test
+    // `draw_indexed_triangles` already used before culling was configurable.
+    pub fn with_front_face(mut self, front_face: FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    // Switches `CullMode` to decide facing direction from each triangle's
+    // view-space normal instead of the default post-projection 2D cross
+    // product. The view-space positions themselves are supplied per-call to
+    // `draw_indexed_triangles_with_view_space_cull`, since the vertex shader
+    // only ever hands back the final clip-space position and this mode needs
+    // to see depth the projection has already discarded.
+    pub fn with_cull_method(mut self, cull_method: CullMethod) -> Self {
+        self.cull_method = cull_method;
+        self
+    }
+
+    // How `draw_indexed_triangles`'s index buffer is interpreted. Defaults to
+    // `TriangleList`.
+    pub fn with_topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    // A strip/fan index value that, when encountered, ends the current run
+    // and starts a fresh one at the next index instead of connecting across
+    // it, so several disconnected strips/fans can be concatenated into one
+    // index buffer. Has no effect under `TriangleList`. See `triangle_indices`.
+    pub fn with_restart_index(mut self, restart_index: usize) -> Self {
+        self.restart_index = Some(restart_index);
+        self
+    }
+
+    pub fn with_depth_test(mut self, depth_buffer: &'a mut DepthBuffer, depth_func: DepthFunc) -> Self {
+        self.depth = Some((depth_buffer, depth_func));
+        self
+    }
+
+    // Selects which NDC z convention gets stored in the depth buffer; see
+    // `DepthRange`. Defaults to `NegativeOneToOne`, matching the raw NDC z
+    // `glm::perspective`/`glm::ortho` already produce.
+    pub fn with_depth_range(mut self, depth_range: DepthRange) -> Self {
+        self.depth_range = depth_range;
+        self
+    }
+
+    // `reference` is compared against the existing stencil value by `func`
+    // to decide whether the fragment survives at all (gating both the depth
+    // test and the color write below it), and is also the value `op` writes
+    // back into the stencil buffer on a pass.
+    pub fn with_stencil_test(mut self, stencil_buffer: &'a mut StencilBuffer, func: StencilFunc, reference: u8, op: StencilOp) -> Self {
+        self.stencil = Some((stencil_buffer, func, reference, op));
+        self
+    }
+
+    // Increments `overdraw_buffer`'s per-pixel counter for every fragment
+    // that passes rasterization, independent of whether the depth test then
+    // rejects it. See `OverdrawBuffer`.
+    pub fn with_overdraw_tracking(mut self, overdraw_buffer: &'a mut OverdrawBuffer) -> Self {
+        self.overdraw = Some(overdraw_buffer);
+        self
+    }
+
+    // Writes `id` into `id_buffer` for every fragment this draw call
+    // shades (gated by the same stencil/depth tests as the color write),
+    // so `IdBuffer::pick` can later report which draw call touched a
+    // given screen pixel. See `IdBuffer`.
+    pub fn with_id_buffer(mut self, id_buffer: &'a mut IdBuffer, id: u32) -> Self {
+        self.id_buffer = Some((id_buffer, id));
+        self
+    }
+
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.fog = Some(fog);
+        self
+    }
+
+    pub fn with_blend_mode(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_raster_backend(mut self, backend: RasterBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    // Back-to-front order is only meaningful once blending is enabled, so a
+    // user reaching for `AlphaBlend` would naturally flip this on alongside it.
+    pub fn with_sort_transparent(mut self, sort_transparent: bool) -> Self {
+        self.sort_transparent = sort_transparent;
+        self
+    }
+
+    // `(x, y, width, height)` in target pixels; rasterization is clamped to the
+    // intersection of this rect and the target bounds. `None` restores the
+    // unclamped, full-target behavior.
+    pub fn set_scissor(&mut self, rect: Option<(u32, u32, u32, u32)>) {
+        self.scissor = rect;
+    }
+
+    pub fn with_viewport(mut self, viewport: Viewport) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_anti_alias(mut self, anti_alias: AntiAlias) -> Self {
+        self.anti_alias = anti_alias;
+        self
+    }
+
+    // When enabled, `draw_indexed_triangles` overlays the post-clip polygon
+    // edges of every triangle in a fixed debug color, after the normal
+    // shaded render, so near-plane (and frustum-side) clipping is visible
+    // exactly where it happens instead of only inferred from missing geometry.
+    pub fn with_debug_clip(mut self, debug_clip: bool) -> Self {
+        self.debug_clip = debug_clip;
+        self
+    }
+
+    // When set, any post-clip triangle whose screen-space area exceeds this
+    // threshold is recursively split into four sub-triangles (midpoint
+    // subdivision of both position and `V`) until it's under the threshold
+    // or `MAX_TESSELLATION_DEPTH` is hit. Large, near-camera triangles are
+    // where affine attribute interpolation error (without perspective
+    // correction) and per-scanline cost are both worst, so this trades a
+    // few extra small triangles for finer, cheaper-to-rasterize ones.
+    pub fn with_max_triangle_area(mut self, max_triangle_area: Option<f32>) -> Self {
+        self.max_triangle_area = max_triangle_area;
+        self
+    }
+
+    // Which point within each pixel the rasterizer samples at. Defaults to
+    // `SampleConvention::TOP_LEFT`, matching this rasterizer's original
+    // behavior; switch to `SampleConvention::PIXEL_CENTER` (or a custom
+    // offset) to match golden images from a renderer using that convention.
+    pub fn with_sample_convention(mut self, sample_convention: SampleConvention) -> Self {
+        self.sample_convention = sample_convention;
+        self
+    }
+
+    // Chooses how a non-antialiased fragment's coverage is decided; see
+    // `CoverageTest`.
+    pub fn with_coverage_test(mut self, coverage_test: CoverageTest) -> Self {
+        self.coverage_test = coverage_test;
+        self
+    }
+
+    // Chooses whether a triangle's color is interpolated per-pixel or shaded
+    // once from its provoking vertex; see `ShadeModel`.
+    pub fn with_shade_model(mut self, shade_model: ShadeModel) -> Self {
+        self.shade_model = shade_model;
+        self
+    }
+
+    // Replaces the pixel shader's output with a debugging visualization for
+    // the duration of subsequent draws; see `DebugOutput`. Defaults to
+    // `Normal`, i.e. the pixel shader runs as normal.
+    pub fn with_debug_output(mut self, debug_output: DebugOutput) -> Self {
+        self.debug_output = debug_output;
+        self
+    }
+
+    // When `true` (ignored under `AntiAlias::Coverage`, which already makes
+    // this decision per-pixel), a pixel is covered as soon as the triangle
+    // touches its cell at all rather than only when the sample point does —
+    // the same half-pixel-expanded edge test `edge_coverage` uses for
+    // antialiasing, but painted at full opacity instead of blended. Useful
+    // for voxelization and similar techniques where a thin triangle must
+    // never drop a pixel it merely grazes.
+    pub fn with_conservative_rasterization(mut self, conservative: bool) -> Self {
+        self.conservative = conservative;
+        self
+    }
+
+    // Rejects a whole triangle before rasterizing a single pixel of it if
+    // `hi_z` (a snapshot of the depth buffer taken before this draw call,
+    // typically from earlier front-to-back-sorted opaque geometry) shows
+    // every pixel the triangle's screen-space bounding box could touch is
+    // already occupied by something nearer. Only sound with a `Less` or
+    // `LessEqual` depth test, since that's the convention `hi_z`'s stored
+    // *maximum* depths assume "nearer wins" against; with any other
+    // `DepthFunc` (or no depth test at all) this is left inert rather than
+    // risk rejecting a triangle that should have passed.
+    pub fn with_hierarchical_z(mut self, hi_z: &'a HierarchicalDepthBuffer) -> Self {
+        self.hierarchical_z = Some(hi_z);
+        self
+    }
+
+    // Discards every fragment on the negative side of a user-defined plane,
+    // like `glClipPlane`: `plane.xyz` is the plane normal and `plane.w` the
+    // offset, tested as `dot(plane.xyz, fragment_position) + plane.w >= 0`
+    // against each fragment's interpolated `V::position`. Meant for section
+    // cutaways, e.g. slicing a cube open to see triangles a full mesh would
+    // otherwise hide behind its own surface.
+    pub fn with_clip_plane(mut self, plane: glm::Vec4) -> Self {
+        self.clip_plane = Some(plane);
+        self
+    }
+
+    // A snapshot of the counters accumulated since the last `reset_stats`
+    // (or since construction), for profiling why a scene is slow.
+    pub fn stats(&self) -> RenderStats {
+        self.stats.snapshot()
+    }
+
+    // Zeroes every counter, typically called once per frame before drawing.
+    pub fn reset_stats(&mut self) {
+        self.stats.reset();
+    }
+
+    // Encodes the shader's RGB output with the sRGB transfer curve before it
+    // reaches the target, so lit midtones match what a display expects instead
+    // of looking too dark when treated as if it were already sRGB-encoded.
+    pub fn with_output_srgb(mut self, output_srgb: bool) -> Self {
+        self.output_srgb = output_srgb;
+        self
+    }
+
+    // When false, rasterization still performs the depth compare and write
+    // but skips the pixel shader call and the color write entirely. Paired
+    // with `DepthFunc::Equal` in a second pass, this turns an expensive
+    // shader from running once per overdrawn fragment into running once per
+    // visible pixel: a cheap depth-only pre-pass establishes which fragment
+    // wins each pixel, then the real pass only shades the survivors.
+    pub fn with_color_write(mut self, color_write: bool) -> Self {
+        self.color_write = color_write;
+        self
+    }
+
+    pub fn draw_indexed_triangles(&mut self, indices: &[usize], vertices: &[V]) {
+        self.draw_indexed_triangles_impl(indices, vertices, None::<fn(&V) -> glm::Vec3>);
+    }
+
+    // Same as `draw_indexed_triangles`, but for use with
+    // `CullMethod::ViewSpaceNormal` (see `with_cull_method`): `view_position`
+    // maps a vertex to its pre-projection (camera-space) position, which the
+    // vertex shader doesn't otherwise expose once it's been projected away.
+    pub fn draw_indexed_triangles_with_view_space_cull(&mut self, indices: &[usize], vertices: &[V], view_position: impl Fn(&V) -> glm::Vec3) {
+        self.draw_indexed_triangles_impl(indices, vertices, Some(view_position));
+    }
+
+    // Rejects the whole mesh in one check against `frustum` before doing any
+    // per-triangle work, using a bounding sphere already transformed into
+    // the same space the frustum's planes were extracted in (typically
+    // world space, with `frustum` built from the camera's view-projection).
+    pub fn draw_indexed_triangles_with_frustum_cull(&mut self, indices: &[usize], vertices: &[V], frustum: &Frustum, bounds: &BoundingSphere) {
+        if !frustum.intersects_sphere(bounds) {
+            return;
+        }
+        self.draw_indexed_triangles(indices, vertices);
+    }
+
+    // Draws `vertices`/`indices` once per instance, transforming a fresh
+    // copy of the base vertices by that instance's model matrix before
+    // shading rather than requiring a separate `draw_indexed_triangles`
+    // call (and a separately captured vertex shader) per instance.
+    pub fn draw_indexed_instanced(&mut self, indices: &[usize], vertices: &[V], instance_count: usize, per_instance: impl Fn(usize) -> glm::Mat4) where V: Transform {
+        for instance in 0..instance_count {
+            let model = per_instance(instance);
+            let instance_vertices: Vec<V> = vertices.iter().map(|v| v.transformed(&model)).collect();
+            self.draw_indexed_triangles(indices, &instance_vertices);
+        }
+    }
+
+    // Draws a quad as the two triangles `(v0, v1, v2)` and `(v0, v2, v3)`,
+    // so callers building 2D/UI geometry (typically alongside the ortho
+    // camera) don't have to hand-write the split themselves.
+    pub fn draw_quad(&mut self, v0: V, v1: V, v2: V, v3: V) {
+        self.draw_indexed_triangles(&[0, 1, 2, 0, 2, 3], &[v0, v1, v2, v3]);
+    }
+
+    // Draws a convex polygon as a fan of triangles anchored at `verts[0]`,
+    // i.e. `(verts[0], verts[i], verts[i + 1])` for each `i` in `1..verts.len() - 1`.
+    // A concave polygon will rasterize incorrectly, same as a hand-built fan would.
+    pub fn draw_polygon(&mut self, verts: &[V]) {
+        if verts.len() < 3 {
+            return;
+        }
+        let indices: Vec<usize> = (1..verts.len() - 1).flat_map(|i| [0, i, i + 1]).collect();
+        self.draw_indexed_triangles(&indices, verts);
+    }
+
+    // Immediate-mode submission: `f` emits geometry into a `TriangleSink`
+    // instead of the caller pre-building index/vertex arrays, which is handy
+    // for procedural/debug shapes assembled on the fly. Internally this is
+    // just the sink's contents batched into one `draw_indexed_triangles` call.
+    pub fn draw_with(&mut self, mut f: impl FnMut(&mut TriangleSink<V>)) {
+        let mut sink = TriangleSink::new();
+        f(&mut sink);
+        self.draw_indexed_triangles(&sink.indices, &sink.vertices);
+    }
+
+    // Draws a single triangle given already-shaded clip-space (position,
+    // attribute) pairs, skipping the vertex shader entirely - the same
+    // clip/cull/viewport-transform/fill pipeline `draw_indexed_triangles`
+    // runs per triangle, minus needing to build index/vertex arrays first.
+    // Handy for one-off triangles (e.g. in a test, or a debug overlay) where
+    // the clip-space position is already known.
+    pub fn fill_triangle(&mut self, a: (glm::Vec4, V), b: (glm::Vec4, V), c: (glm::Vec4, V)) {
+        self.stats.triangles_submitted.fetch_add(1, Ordering::Relaxed);
+        let mut screen_triangles = Vec::new();
+        let mut debug_clip_polygons = Vec::new();
+        self.process_triangle(
+            [a.0, b.0, c.0], [a.1, b.1, c.1], &None::<fn(&V) -> glm::Vec3>,
+            &mut screen_triangles, &mut debug_clip_polygons
+        );
+        self.dispatch_screen_triangles(&mut screen_triangles, &debug_clip_polygons);
+    }
+
+    fn draw_indexed_triangles_impl<CV: Fn(&V) -> glm::Vec3>(&mut self, indices: &[usize], vertices: &[V], view_position: Option<CV>) {
+        // Reused across calls instead of reallocating `vertices.to_vec()`
+        // and a fresh positions `Vec` every draw/instance.
+        self.scratch_vertices.clear();
+        self.scratch_vertices.extend_from_slice(vertices);
+
+        self.scratch_positions.clear();
+        let vertex_shader = &self.vertex_shader;
+        self.scratch_positions.extend(
+            self.scratch_vertices.iter_mut().map(|v| vertex_shader.shade(v))
+        );
+        // Clip and cull every triangle up front so the raster backend below
+        // (scanline or tiled) can dispatch over a flat, already-screen-space list.
+        let mut screen_triangles = Vec::new();
+        let mut debug_clip_polygons = Vec::new();
+        for (i0, i1, i2) in Self::triangle_indices(self.topology, indices, self.restart_index) {
+            self.stats.triangles_submitted.fetch_add(1, Ordering::Relaxed);
+            let (Some(p0), Some(p1), Some(p2)) =
+                (self.scratch_positions[i0], self.scratch_positions[i1], self.scratch_positions[i2]) else {
+                continue;
+            };
+            let clip_positions = [p0, p1, p2];
+            let clip_vertices = [self.scratch_vertices[i0], self.scratch_vertices[i1], self.scratch_vertices[i2]];
+            self.process_triangle(clip_positions, clip_vertices, &view_position, &mut screen_triangles, &mut debug_clip_polygons);
+        }
+
+        self.dispatch_screen_triangles(&mut screen_triangles, &debug_clip_polygons);
+    }
+
+    // Clips one triangle against the frustum, then culls/viewport-transforms
+    // (and tessellates, if `max_triangle_area` is set) each resulting
+    // clip-fan triangle into `screen_triangles`, ready for `dispatch_screen_triangles`.
+    fn process_triangle<CV: Fn(&V) -> glm::Vec3>(
+        &mut self,
+        clip_positions: [glm::Vec4; 3], clip_vertices: [V; 3],
+        view_position: &Option<CV>,
+        screen_triangles: &mut Vec<(glm::Vec4, glm::Vec4, glm::Vec4, V, V, V)>,
+        debug_clip_polygons: &mut Vec<Vec<glm::Vec4>>
+    ) {
+        let (clipped_positions, clipped_vertices) =
+            Self::clip_polygon(&clip_positions, &clip_vertices);
+        if clipped_positions.len() != 3 {
+            self.stats.triangles_clipped.fetch_add(1, Ordering::Relaxed);
+        }
+        if clipped_positions.len() < 3 {
+            return;
+        }
+        if self.debug_clip {
+            debug_clip_polygons.push(
+                clipped_positions.iter().map(|p| self.transform_to_target_coordinates(&(p / p.w))).collect::<Vec<_>>()
+            );
+        }
+        for i in 1..clipped_positions.len() - 1 {
+            let mut p0 = clipped_positions[0];
+            let mut p1 = clipped_positions[i];
+            let mut p2 = clipped_positions[i + 1];
+            let v0 = clipped_vertices[0];
+            let v1 = clipped_vertices[i];
+            let v2 = clipped_vertices[i + 1];
+            p0 /= p0.w;
+            p1 /= p1.w;
+            p2 /= p2.w;
+            if self.cull_mode != CullMode::None {
+                let facing_sign = match (&self.cull_method, view_position) {
+                    (CullMethod::ViewSpaceNormal, Some(view_position)) => {
+                        let a = view_position(&v0);
+                        let b = view_position(&v1);
+                        let c = view_position(&v2);
+                        // In view space the camera sits at the origin
+                        // looking down -Z, so +Z always points back
+                        // toward it regardless of how far away the
+                        // triangle is; a CCW-wound face's normal points
+                        // toward +Z.
+                        glm::dot(&glm::cross(&(b - a), &(c - a)), &glm::vec3(0.0, 0.0, 1.0))
+                    },
+                    _ => {
+                        let d0 = p2 - p0;
+                        let d1 = p2 - p1;
+                        (d0.x * d1.y) - (d0.y * d1.x)
+                    }
+                };
+                let is_front_facing = match self.front_face {
+                    FrontFace::CounterClockwise => facing_sign >= 0.0,
+                    FrontFace::Clockwise => facing_sign <= 0.0
+                };
+                let should_cull = match self.cull_mode {
+                    CullMode::None => false,
+                    CullMode::Front => is_front_facing,
+                    CullMode::Back => !is_front_facing
+                };
+                if should_cull {
+                    self.stats.triangles_culled.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+            }
+            let p0 = self.transform_to_target_coordinates(&p0);
+            let p1 = self.transform_to_target_coordinates(&p1);
+            let p2 = self.transform_to_target_coordinates(&p2);
+
+            // Cheap reject before the (potentially recursive) split below:
+            // a triangle whose screen-space bbox misses both the target
+            // and the scissor rect can't produce a single fragment.
+            let bbox_min_x = p0.x.min(p1.x).min(p2.x);
+            let bbox_max_x = p0.x.max(p1.x).max(p2.x);
+            let bbox_min_y = p0.y.min(p1.y).min(p2.y);
+            let bbox_max_y = p0.y.max(p1.y).max(p2.y);
+            let (scissor_x, scissor_y, scissor_width, scissor_height) = self.scissor
+                .unwrap_or((0, 0, self.target.size.0, self.target.size.1));
+            let area_min_x = scissor_x as f32;
+            let area_max_x = (scissor_x + scissor_width).min(self.target.size.0) as f32;
+            let area_min_y = scissor_y as f32;
+            let area_max_y = (scissor_y + scissor_height).min(self.target.size.1) as f32;
+            if bbox_max_x < area_min_x || bbox_min_x > area_max_x ||
+                bbox_max_y < area_min_y || bbox_min_y > area_max_y {
+                self.stats.triangles_offscreen.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
+            match self.max_triangle_area {
+                Some(max_triangle_area) =>
+                    Self::tessellate_triangle(max_triangle_area, 0, p0, p1, p2, v0, v1, v2, screen_triangles),
+                None => screen_triangles.push((p0, p1, p2, v0, v1, v2))
+            }
+        }
+    }
+
+    // Sorts (if `sort_transparent`) and rasterizes a batch of already
+    // clipped/culled/viewport-transformed triangles through the active
+    // `RasterBackend`, then draws any `debug_clip` outlines over them.
+    fn dispatch_screen_triangles(
+        &mut self,
+        screen_triangles: &mut Vec<(glm::Vec4, glm::Vec4, glm::Vec4, V, V, V)>,
+        debug_clip_polygons: &[Vec<glm::Vec4>]
+    ) {
+        if self.sort_transparent {
+            // NDC z increases with distance from the camera, so sorting descending
+            // by the triangle's average depth draws the farthest triangle first.
+            screen_triangles.sort_by(|a, b| {
+                let depth = |p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4| (p0.z + p1.z + p2.z) / 3.0;
+                depth(&b.0, &b.1, &b.2).partial_cmp(&depth(&a.0, &a.1, &a.2)).unwrap()
+            });
+        }
+
+        match self.backend {
+            RasterBackend::Scanline => {
+                for (p0, p1, p2, v0, v1, v2) in screen_triangles.iter() {
+                    self.draw_triangle(p0, p1, p2, v0, v1, v2);
+                }
+            },
+            RasterBackend::Tiled { tile_size } => self.draw_triangles_tiled(screen_triangles, tile_size),
+            RasterBackend::EdgeFunction => {
+                for (p0, p1, p2, v0, v1, v2) in screen_triangles.iter() {
+                    self.draw_triangle_edge_function(p0, p1, p2, v0, v1, v2);
+                }
+            }
+        }
+
+        if self.debug_clip {
+            for polygon in debug_clip_polygons {
+                self.draw_debug_polygon_outline(polygon, DEBUG_CLIP_COLOR);
+            }
+        }
+    }
+
+    // Bounds the recursion in `tessellate_triangle` so a degenerate area
+    // threshold (e.g. 0.0) can't recurse until the stack overflows; four
+    // levels already turns one triangle into up to 256.
+    const MAX_TESSELLATION_DEPTH: u32 = 4;
+
+    // Recursively splits `(p0, p1, p2)` into four sub-triangles by
+    // midpoint subdivision of both the screen-space positions and the
+    // `Linear` vertex attributes, until its screen-space area is under
+    // `max_area` or `MAX_TESSELLATION_DEPTH` is reached, pushing every leaf
+    // triangle into `out` in the same tuple shape `draw_indexed_triangles_impl`
+    // pushes untessellated triangles in.
+    #[allow(clippy::too_many_arguments)]
+    fn tessellate_triangle(
+        max_area: f32, depth: u32,
+        p0: glm::Vec4, p1: glm::Vec4, p2: glm::Vec4,
+        v0: V, v1: V, v2: V,
+        out: &mut Vec<(glm::Vec4, glm::Vec4, glm::Vec4, V, V, V)>
+    ) {
+        let area = 0.5 * ((p1.x - p0.x) * (p2.y - p0.y) - (p2.x - p0.x) * (p1.y - p0.y)).abs();
+        if depth >= Self::MAX_TESSELLATION_DEPTH || area <= max_area {
+            out.push((p0, p1, p2, v0, v1, v2));
+            return;
+        }
+
+        let p01 = p0 + (p1 - p0) * 0.5;
+        let p12 = p1 + (p2 - p1) * 0.5;
+        let p20 = p2 + (p0 - p2) * 0.5;
+        let v01 = v0 + (v1 - v0) * 0.5;
+        let v12 = v1 + (v2 - v1) * 0.5;
+        let v20 = v2 + (v0 - v2) * 0.5;
+
+        let next_depth = depth + 1;
+        Self::tessellate_triangle(max_area, next_depth, p0, p01, p20, v0, v01, v20, out);
+        Self::tessellate_triangle(max_area, next_depth, p01, p1, p12, v01, v1, v12, out);
+        Self::tessellate_triangle(max_area, next_depth, p20, p12, p2, v20, v12, v2, out);
+        Self::tessellate_triangle(max_area, next_depth, p01, p12, p20, v01, v12, v20, out);
+    }
+
+    // Plots the edges of an already screen-space polygon directly into the
+    // target with a fixed color, independent of the vertex shader's `V` and
+    // the active pixel shader. Used by `debug_clip` to show exactly which
+    // edges the clipper produced, which the normal shaded render can't
+    // distinguish from an edge the original triangle already had.
+    fn draw_debug_polygon_outline(&mut self, polygon: &[glm::Vec4], color: [u8; 4]) {
+        for i in 0..polygon.len() {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            Self::draw_debug_line_segment(self.target, a.x, a.y, b.x, b.y, color);
+        }
+    }
+
+    // Plain Bresenham, bypassing the pixel shader entirely since this draws a
+    // fixed overlay color rather than an interpolated vertex attribute.
+    fn draw_debug_line_segment(target: &mut TextureBuffer<'_>, x0: f32, y0: f32, x1: f32, y1: f32, color: [u8; 4]) {
+        let mut x0 = x0.round() as i32;
+        let mut y0 = y0.round() as i32;
+        let x1 = x1.round() as i32;
+        let y1 = y1.round() as i32;
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+
+        loop {
+            target.set_checked((x0 as u32, y0 as u32), &color);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+    }
+
+    // For debug geometry like axes, grids, and normals-as-lines. Runs the
+    // vertex shader and perspective divide like `draw_indexed_triangles`,
+    // then clips each segment to the target rectangle with Cohen-Sutherland
+    // before rasterizing it with Bresenham, interpolating `V` along the way.
+    pub fn draw_indexed_lines(&mut self, indices: &[usize], vertices: &[V], topology: LineTopology) {
+        let mut vertices = vertices.to_vec();
+        let positions = vertices.
+            iter_mut().
+            map(|v| self.vertex_shader.shade(v)).
+            collect::<Vec<_>>();
+
+        let width = self.target.size.0 as f32;
+        let height = self.target.size.1 as f32;
+
+        for (i0, i1) in Self::line_indices(topology, indices) {
+            let (Some(p0), Some(p1)) = (positions[i0], positions[i1]) else {
+                continue;
+            };
+            // A segment with an endpoint behind the eye would need proper
+            // near-plane clipping to divide safely; skip it rather than
+            // rasterizing a line that's shot off to infinity.
+            if p0.w <= 0.0 || p1.w <= 0.0 {
+                continue;
+            }
+            let screen0 = self.transform_to_target_coordinates(&(p0 / p0.w));
+            let screen1 = self.transform_to_target_coordinates(&(p1 / p1.w));
+
+            if let Some(((x0, y0, v0), (x1, y1, v1))) = Self::clip_line_to_target(
+                (screen0.x, screen0.y, vertices[i0]),
+                (screen1.x, screen1.y, vertices[i1]),
+                width, height
+            ) {
+                match self.anti_alias {
+                    AntiAlias::None => self.draw_line(x0, y0, v0, x1, y1, v1),
+                    _ => self.draw_line_aa(x0, y0, v0, x1, y1, v1)
+                }
+            }
+        }
+    }
+
+    // For particle systems and debugging vertex positions. Plots a filled
+    // `size x size` square centered on each vertex's screen position, shaded
+    // once per point since there's no second attribute to interpolate across.
+    pub fn draw_points(&mut self, indices: &[usize], vertices: &[V], size: u32) {
+        let mut vertices = vertices.to_vec();
+        let positions = vertices.
+            iter_mut().
+            map(|v| self.vertex_shader.shade(v)).
+            collect::<Vec<_>>();
+
+        for &i in indices {
+            let Some(p) = positions[i] else {
+                continue;
+            };
+            if p.w <= 0.0 {
+                continue;
+            }
+            let screen = self.transform_to_target_coordinates(&(p / p.w));
+            self.draw_point(screen.x, screen.y, vertices[i], size);
+        }
+    }
+
+    fn draw_point(&mut self, x: f32, y: f32, v: V, size: u32) {
+        let Some(color) = self.pixel_shader.shade(&v) else {
+            return;
+        };
+        let half = (size / 2) as i32;
+        let cx = x.round() as i32;
+        let cy = y.round() as i32;
+
+        for dy in 0..size as i32 {
+            for dx in 0..size as i32 {
+                let px = cx - half + dx;
+                let py = cy - half + dy;
+                if px >= 0 && py >= 0 {
+                    self.target.set_checked((px as u32, py as u32), &color);
+                }
+            }
+        }
+    }
+
+    // Bins each triangle into every tile its screen-space bounding box overlaps,
+    // then rasterizes tiles in parallel. Tiles are disjoint rectangles of the
+    // target, so unlike the scanline backend's per-row split this also scales
+    // with triangle count rather than just framebuffer height.
+    fn draw_triangles_tiled(&mut self, triangles: &[(glm::Vec4, glm::Vec4, glm::Vec4, V, V, V)], tile_size: u32) {
+        let (width, height) = self.target.size;
+        let tiles_x = width.div_ceil(tile_size).max(1);
+        let tiles_y = height.div_ceil(tile_size).max(1);
+
+        let mut bins: Vec<Vec<usize>> = vec![Vec::new(); (tiles_x * tiles_y) as usize];
+        for (index, (p0, p1, p2, ..)) in triangles.iter().enumerate() {
+            let min_x = p0.x.min(p1.x).min(p2.x).max(0.0) as u32;
+            let max_x = p0.x.max(p1.x).max(p2.x).min(width as f32).ceil() as u32;
+            let min_y = p0.y.min(p1.y).min(p2.y).max(0.0) as u32;
+            let max_y = p0.y.max(p1.y).max(p2.y).min(height as f32).ceil() as u32;
+            if min_x >= max_x || min_y >= max_y {
+                continue;
+            }
+
+            for tile_y in (min_y / tile_size)..=((max_y - 1) / tile_size) {
+                for tile_x in (min_x / tile_size)..=((max_x - 1) / tile_size) {
+                    bins[(tile_y * tiles_x + tile_x) as usize].push(index);
+                }
+            }
+        }
+
+        let target_ptr = RowPtr(self.target.buffer.as_mut_ptr());
+        let bytes_per_pixel = self.target.bytes_per_pixel;
+        let depth_ptr = self.depth.as_mut().map(|(depth_buffer, depth_func)| {
+            (RowPtr(depth_buffer.buffer.as_mut_ptr() as *mut u8), *depth_func)
+        });
+        let stencil_ptr = self.stencil.as_mut().map(|(stencil_buffer, func, reference, op)| {
+            (RowPtr(stencil_buffer.buffer.as_mut_ptr()), *func, *reference, *op)
+        });
+        let pixel_shader = &self.pixel_shader;
+        let blend = self.blend;
+        let color_write = self.color_write;
+        let fog = self.fog;
+        let shade_model = self.shade_model;
+        let debug_output = self.debug_output;
+        let depth_range = self.depth_range;
+        let clip_plane = self.clip_plane;
+        let overdraw_ptr = self.overdraw.as_mut().map(|overdraw_buffer| {
+            RowPtr(overdraw_buffer.buffer.as_mut_ptr() as *mut u8)
+        });
+        let id_ptr = self.id_buffer.as_mut().map(|(id_buffer, id)| {
+            (RowPtr(id_buffer.buffer.as_mut_ptr() as *mut u8), *id)
+        });
+
+        bins.into_par_iter().enumerate().for_each(|(tile_index, triangle_indices)| {
+            let tile_index = tile_index as u32;
+            let x_min = (tile_index % tiles_x) * tile_size;
+            let y_min = (tile_index / tiles_x) * tile_size;
+            let x_max = (x_min + tile_size).min(width);
+            let y_max = (y_min + tile_size).min(height);
+
+            for &index in &triangle_indices {
+                let (p0, p1, p2, v0, v1, v2) = &triangles[index];
+                let bary_setup = BarycentricSetup::new(p0, p1, p2);
+                // See `draw_flat_triangle_common`: computed once per triangle
+                // so `Flat` reuses the provoking vertex's shaded color for
+                // every pixel it covers, instead of re-shading per pixel.
+                let flat_color = match (shade_model, debug_output) {
+                    (ShadeModel::Flat, DebugOutput::Normal | DebugOutput::Overdraw) => Some(pixel_shader.shade(v0)),
+                    _ => None
+                };
+                for y in y_min..y_max {
+                    let (_, mut f1, mut f2) = bary_setup.weights_at(x_min as f32, y as f32);
+                    let (step1, step2) = bary_setup.step_x();
+                    for x in x_min..x_max {
+                        let f = (1.0 - f1 - f2, f1, f2);
+                        f1 += step1;
+                        f2 += step2;
+                        if f.0 < 0.0 || f.1 < 0.0 || f.2 < 0.0 {
+                            continue;
+                        }
+
+                        if let Some(plane) = clip_plane {
+                            if Self::clip_plane_rejects(plane, &(*v0 * f.0 + *v1 * f.1 + *v2 * f.2)) {
+                                continue;
+                            }
+                        }
+
+                        // Counted here, before the stencil/depth tests below,
+                        // so it reflects every fragment that survives
+                        // rasterization regardless of whether either test
+                        // then rejects it.
+                        if let Some(overdraw_ptr) = overdraw_ptr {
+                            let overdraw_slot = unsafe {
+                                &mut *(overdraw_ptr.0 as *mut u16).add((y * width + x) as usize)
+                            };
+                            *overdraw_slot += 1;
+                        }
+
+                        if let Some((stencil_ptr, func, reference, op)) = stencil_ptr {
+                            let stencil_slot = unsafe {
+                                &mut *stencil_ptr.0.add((y * width + x) as usize)
+                            };
+                            if !func.passes(reference, *stencil_slot) {
+                                continue;
+                            }
+                            *stencil_slot = op.apply(*stencil_slot, reference);
+                        }
+
+                        let depth_value = p0.z * f.0 + p1.z * f.1 + p2.z * f.2;
+                        let depth_slot = depth_ptr.map(|(depth_ptr, depth_func)| {
+                            let depth_slot = unsafe {
+                                &mut *(depth_ptr.0 as *mut f32).add((y * width + x) as usize)
+                            };
+                            (depth_slot, depth_value, depth_func)
+                        });
+                        if let Some((depth_slot, depth, depth_func)) = &depth_slot {
+                            if !depth_func.passes(*depth, **depth_slot) {
+                                continue;
+                            }
+                        }
+
+                        if !color_write {
+                            if let Some((depth_slot, depth, _)) = depth_slot {
+                                *depth_slot = depth;
+                            }
+                            continue;
+                        }
+
+                        let color = match debug_output {
+                            DebugOutput::Normal | DebugOutput::Overdraw => match flat_color {
+                                Some(color) => color,
+                                None => pixel_shader.shade(&(*v0 * f.0 + *v1 * f.1 + *v2 * f.2))
+                            },
+                            DebugOutput::Depth { near, far } => Some(Self::debug_depth_color(depth_value, depth_range, near, far)),
+                            DebugOutput::Barycentric => Some(Self::debug_barycentric_color(f))
+                        };
+                        let Some(color) = color else {
+                            continue;
+                        };
+                        if let Some((depth_slot, depth, _)) = depth_slot {
+                            *depth_slot = depth;
+                        }
+                        if let Some((id_ptr, id)) = id_ptr {
+                            let id_slot = unsafe {
+                                &mut *(id_ptr.0 as *mut u32).add((y * width + x) as usize)
+                            };
+                            *id_slot = id;
+                        }
+                        let color = match &fog {
+                            Some(fog) => fog.apply(color, depth_value),
+                            None => color
+                        };
+                        let byte_index = (bytes_per_pixel * (y * width + x)) as usize;
+                        let pixel = unsafe { std::slice::from_raw_parts_mut(target_ptr.0.add(byte_index), 4) };
+                        let color = match blend {
+                            BlendMode::Opaque => color,
+                            _ => blend.blend(color, [pixel[0], pixel[1], pixel[2], pixel[3]])
+                        };
+                        pixel.copy_from_slice(&color);
+                    }
+                }
+            }
+        });
+    }
+
+    // Expands an index buffer into independent triangles according to
+    // `topology`. A strip alternates winding every other triangle (the
+    // second, fourth, ... triangle has its first two indices swapped) so
+    // that every triangle in the strip keeps the same front face.
+    //
+    // `restart_index`, if set, splits a strip or fan into independent runs
+    // wherever it appears in `indices` (each run restarting its own winding
+    // and fan-apex state), the way exported mesh data concatenates several
+    // disconnected strips into one buffer without a spurious connecting
+    // triangle between them. A triangle list has no such in-progress state
+    // to reset, so `restart_index` has no effect on it.
+    fn triangle_indices(topology: PrimitiveTopology, indices: &[usize], restart_index: Option<usize>) -> Vec<(usize, usize, usize)> {
+        if let (Some(restart), PrimitiveTopology::TriangleStrip | PrimitiveTopology::TriangleFan) = (restart_index, topology) {
+            return indices.split(|&i| i == restart)
+                .flat_map(|segment| Self::triangle_indices(topology, segment, None))
+                .collect();
+        }
+        if indices.len() < 3 {
+            return Vec::new();
+        }
+        match topology {
+            PrimitiveTopology::TriangleList => indices.chunks_exact(3).map(|c| (c[0], c[1], c[2])).collect(),
+            PrimitiveTopology::TriangleStrip => (0..indices.len() - 2).map(|i| {
+                if i % 2 == 0 {
+                    (indices[i], indices[i + 1], indices[i + 2])
+                } else {
+                    (indices[i + 1], indices[i], indices[i + 2])
+                }
+            }).collect(),
+            PrimitiveTopology::TriangleFan => (1..indices.len() - 1).map(|i| (indices[0], indices[i], indices[i + 1])).collect()
+        }
+    }
+
+    // Expands an index buffer into independent line segments according to `topology`.
+    fn line_indices(topology: LineTopology, indices: &[usize]) -> Vec<(usize, usize)> {
+        match topology {
+            LineTopology::LineList => indices.chunks_exact(2).map(|c| (c[0], c[1])).collect(),
+            LineTopology::LineStrip => indices.windows(2).map(|c| (c[0], c[1])).collect()
+        }
+    }
+
+    // Cohen-Sutherland clipping of a screen-space segment against the
+    // `[0, width] x [0, height]` target rectangle, carrying the interpolated
+    // `V` attributes at each clipped endpoint along with the position.
+    #[allow(clippy::type_complexity)]
+    fn clip_line_to_target(p0: (f32, f32, V), p1: (f32, f32, V), width: f32, height: f32)
+        -> Option<((f32, f32, V), (f32, f32, V))> {
+        let (mut x0, mut y0, mut v0) = p0;
+        let (mut x1, mut y1, mut v1) = p1;
+        const INSIDE: u8 = 0;
+        const LEFT: u8 = 1;
+        const RIGHT: u8 = 2;
+        const TOP: u8 = 4;
+        const BOTTOM: u8 = 8;
+
+        let out_code = |x: f32, y: f32| {
+            let mut code = INSIDE;
+            if x < 0.0 { code |= LEFT; } else if x > width { code |= RIGHT; }
+            if y < 0.0 { code |= TOP; } else if y > height { code |= BOTTOM; }
+            code
+        };
+
+        let mut code0 = out_code(x0, y0);
+        let mut code1 = out_code(x1, y1);
+
+        loop {
+            if code0 | code1 == INSIDE {
+                return Some(((x0, y0, v0), (x1, y1, v1)));
+            }
+            if code0 & code1 != INSIDE {
+                return None;
+            }
+
+            let code_out = if code0 != INSIDE { code0 } else { code1 };
+            let (x, y, t) = if code_out & TOP != 0 {
+                let t = -y0 / (y1 - y0);
+                (x0 + (x1 - x0) * t, 0.0, t)
+            } else if code_out & BOTTOM != 0 {
+                let t = (height - y0) / (y1 - y0);
+                (x0 + (x1 - x0) * t, height, t)
+            } else if code_out & RIGHT != 0 {
+                let t = (width - x0) / (x1 - x0);
+                (width, y0 + (y1 - y0) * t, t)
+            } else {
+                let t = -x0 / (x1 - x0);
+                (0.0, y0 + (y1 - y0) * t, t)
+            };
+            let v = v0 + (v1 - v0) * t;
+
+            if code_out == code0 {
+                x0 = x; y0 = y; v0 = v;
+                code0 = out_code(x0, y0);
+            } else {
+                x1 = x; y1 = y; v1 = v;
+                code1 = out_code(x1, y1);
+            }
+        }
+    }
+
+    // Rasterizes a single clipped screen-space segment with Bresenham,
+    // interpolating `V` by step count along the way.
+    fn draw_line(&mut self, x0: f32, y0: f32, v0: V, x1: f32, y1: f32, v1: V) {
+        let mut x0 = x0.round() as i32;
+        let mut y0 = y0.round() as i32;
+        let x1 = x1.round() as i32;
+        let y1 = y1.round() as i32;
+
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let sx = if x0 < x1 { 1 } else { -1 };
+        let sy = if y0 < y1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        let steps = dx.max(-dy).max(1) as f32;
+        let mut step = 0.0;
+
+        loop {
+            let color = self.pixel_shader.shade(&(v0 + (v1 - v0) * (step / steps)));
+            if let Some(color) = color {
+                self.target.set_checked((x0 as u32, y0 as u32), &color);
+            }
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+            step += 1.0;
+        }
+    }
+
+    // Blends a single line pixel against the destination, the same way the
+    // triangle rasterizer composites a fractionally-covered fragment: run it
+    // through the render context's `BlendMode`, then lerp the result against
+    // whatever was already there by `coverage`.
+    fn blend_line_pixel(&mut self, x: i32, y: i32, color: [u8; 4], coverage: f32) {
+        if x < 0 || y < 0 || coverage <= 0.0 {
+            return;
+        }
+        let point = (x as u32, y as u32);
+        if point.0 >= self.target.size.0 || point.1 >= self.target.size.1 {
+            return;
+        }
+        let dst = self.target.get(point);
+        let color = match self.blend {
+            BlendMode::Opaque => color,
+            _ => self.blend.blend(color, dst)
+        };
+        let color = if coverage < 1.0 {
+            let mut out = [0u8; 4];
+            for i in 0..4 {
+                out[i] = (color[i] as f32 * coverage + dst[i] as f32 * (1.0 - coverage)).round() as u8;
+            }
+            out
+        } else {
+            color
+        };
+        self.target.set(point, &color);
+    }
+
+    // Xiaolin Wu's antialiased line algorithm: at each step along the major
+    // axis the ideal line falls between two rows (or columns) of pixels, so
+    // shade both straddling pixels, weighting each by how close the line
+    // passes to it, instead of snapping to a single jagged Bresenham pixel.
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, v0: V, x1: f32, y1: f32, v1: V) {
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (mut x0, mut y0, mut x1, mut y1, mut v0, mut v1) = if steep {
+            (y0, x0, y1, x1, v0, v1)
+        } else {
+            (x0, y0, x1, y1, v0, v1)
+        };
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+            std::mem::swap(&mut v0, &mut v1);
+        }
+
+        let dx = x1 - x0;
+        let dy = y1 - y0;
+        let gradient = if dx == 0.0 { 0.0 } else { dy / dx };
+        let steps = dx.max(1.0);
+
+        let mut x = x0;
+        let mut y = y0;
+        let mut step = 0.0;
+        while x <= x1 {
+            let v = v0 + (v1 - v0) * (step / steps);
+            if let Some(color) = self.pixel_shader.shade(&v) {
+                let y_floor = y.floor();
+                let coverage_upper = 1.0 - (y - y_floor);
+                let (lower, upper) = (y_floor as i32, y_floor as i32 + 1);
+
+                if steep {
+                    self.blend_line_pixel(lower, x as i32, color, coverage_upper);
+                    self.blend_line_pixel(upper, x as i32, color, 1.0 - coverage_upper);
+                } else {
+                    self.blend_line_pixel(x as i32, lower, color, coverage_upper);
+                    self.blend_line_pixel(x as i32, upper, color, 1.0 - coverage_upper);
+                }
+            }
+            x += 1.0;
+            y += gradient;
+            step += 1.0;
+        }
+    }
+
+    // Clips a convex polygon against a single clip-space plane using Sutherland-Hodgman,
+    // where `distance` is positive on the inside of the plane and the intersection is
+    // found by linearly interpolating where it crosses zero.
+    fn clip_against_plane(positions: &[glm::Vec4], vertices: &[V], distance: impl Fn(&glm::Vec4) -> f32)
+        -> (Vec<glm::Vec4>, Vec<V>) {
+        let n = positions.len();
+        let mut out_positions = Vec::with_capacity(n + 1);
+        let mut out_vertices = Vec::with_capacity(n + 1);
+
+        for i in 0..n {
+            let cur_p = positions[i];
+            let cur_v = vertices[i];
+            let prev_p = positions[(i + n - 1) % n];
+            let prev_v = vertices[(i + n - 1) % n];
+
+            let cur_dist = distance(&cur_p);
+            let prev_dist = distance(&prev_p);
+
+            let cur_inside = cur_dist >= 0.0;
+            let prev_inside = prev_dist >= 0.0;
+
+            if cur_inside != prev_inside {
+                let t = prev_dist / (prev_dist - cur_dist);
+                out_positions.push(prev_p + (cur_p - prev_p) * t);
+                out_vertices.push(prev_v + (cur_v - prev_v) * t);
+            }
+            if cur_inside {
+                out_positions.push(cur_p);
+                out_vertices.push(cur_v);
+            }
+        }
+
+        (out_positions, out_vertices)
+    }
+
+    // Clips a triangle against all six clip-space planes (`-w <= x,y,z <= w`), returning
+    // the resulting convex polygon as a vertex fan the caller can triangulate.
+    fn clip_polygon(positions: &[glm::Vec4; 3], vertices: &[V; 3]) -> (Vec<glm::Vec4>, Vec<V>) {
+        let planes: [fn(&glm::Vec4) -> f32; 6] = [
+            |p| p.w + p.x,
+            |p| p.w - p.x,
+            |p| p.w + p.y,
+            |p| p.w - p.y,
+            |p| p.w + p.z,
+            |p| p.w - p.z
+        ];
+
+        let mut positions = positions.to_vec();
+        let mut vertices = vertices.to_vec();
+
+        for plane in &planes {
+            if positions.is_empty() {
+                break;
+            }
+            let (clipped_positions, clipped_vertices) =
+                Self::clip_against_plane(&positions, &vertices, plane);
+            positions = clipped_positions;
+            vertices = clipped_vertices;
+        }
+
+        (positions, vertices)
+    }
+
+
+    fn draw_triangle(&mut self,
+        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
+        v0: &V, v1: &V, v2: &V) {
+        // An edge-on or otherwise collinear triangle has (near) zero signed
+        // area, which would later send `BarycentricSetup`'s `inv_area` and
+        // the flat-triangle slopes (`dx / dy` with `dy == 0`) to
+        // infinity/NaN. Bail out here rather than let that propagate into
+        // interpolated attributes or pixel coordinates.
+        const DEGENERATE_AREA_EPSILON: f32 = 1e-6;
+        let signed_area = (p1.x - p0.x) * (p2.y - p0.y) - (p2.x - p0.x) * (p1.y - p0.y);
+        if signed_area.abs() < DEGENERATE_AREA_EPSILON {
+            return;
+        }
+
+        if let Some(hi_z) = self.hierarchical_z {
+            let depth_func = self.depth.as_ref().map(|(_, depth_func)| *depth_func);
+            if matches!(depth_func, Some(DepthFunc::Less) | Some(DepthFunc::LessEqual)) {
+                let x0 = p0.x.min(p1.x).min(p2.x).floor().max(0.0) as u32;
+                let x1 = p0.x.max(p1.x).max(p2.x).ceil().min(self.target.size.0 as f32) as u32;
+                let y0 = p0.y.min(p1.y).min(p2.y).floor().max(0.0) as u32;
+                let y1 = p0.y.max(p1.y).max(p2.y).ceil().min(self.target.size.1 as f32) as u32;
+                let nearest_z = p0.z.min(p1.z).min(p2.z);
+                if nearest_z > hi_z.max_depth_over(x0, y0, x1, y1) {
+                    self.stats.triangles_occlusion_culled.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+        }
+
+        let mut p0 = p0;
+        let mut p1 = p1;
+        let mut p2 = p2;
+        let mut v0 = v0;
+        let mut v1 = v1;
+        let mut v2 = v2;
+
+        if p1.y < p0.y {
+            std::mem::swap(&mut p0, &mut p1);
+            std::mem::swap(&mut v0, &mut v1);
+        }
+        if p2.y < p1.y {
+            std::mem::swap(&mut p1, &mut p2);
+            std::mem::swap(&mut v1, &mut v2);
+        }
+        if p1.y < p0.y {
+            std::mem::swap(&mut p0, &mut p1);
+            std::mem::swap(&mut v0, &mut v1);
+        }
+
+        //natural flat top
+        if p0.y == p1.y { 
+            if p1.x < p0.x {
+                std::mem::swap(&mut p0, &mut p1);
+                std::mem::swap(&mut v0, &mut v1);
+            }
+            self.draw_flat_top_triangle(p0, p1, p2, v0, v1, v2);
+        }
+        //natural flat bottom
+        else if p1.y == p2.y {
+            if p2.x < p1.x {
+                std::mem::swap(&mut p1, &mut p2);
+                std::mem::swap(&mut v1, &mut v2);
+            }
+            self.draw_flat_bottom_triangle(p0, p1, p2, v0, v1, v2);
+        }
+        //general triangle
+        else {
+            let alpha = (p1.y - p0.y) / (p2.y - p0.y);
+            let pi = p0 + (p2 - p0) * alpha;
+            let vi = *v0 + (*v2 - *v0) * alpha;
+            //major right
+            if p1.x < pi.x {
+                self.draw_flat_bottom_triangle(p0, p1, &pi, v0, v1, &vi);
+                self.draw_flat_top_triangle(p1, &pi, p2, v1, &vi, v2);
+            }
+            //major left
+            else {
+                self.draw_flat_bottom_triangle(p0, &pi, p1, v0, &vi, v1);
+                self.draw_flat_top_triangle(&pi, p1, p2, &vi, v1, v2);
+            }
+        }
+    }
+
+    fn draw_flat_top_triangle(&mut self, 
+        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
+        v0: &V, v1: &V, v2: &V) {
+
+        let slope1 = (p2.x - p0.x) / (p2.y - p0.y);
+        let slope2 = (p2.x - p1.x) / (p2.y - p1.y);
+
+        self.draw_flat_triangle_common(p0, p1, p2, [(slope1, p0), (slope2, p1)], v0, v1, v2);
+    }
+
+    fn draw_flat_bottom_triangle(&mut self, 
+        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
+        v0: &V, v1: &V, v2: &V) {
+
+        let slope1 = (p1.x - p0.x) / (p1.y - p0.y);
+        let slope2 = (p2.x - p0.x) / (p2.y - p0.y);
+
+        self.draw_flat_triangle_common(p0, p1, p2, [(slope1, p0), (slope2, p0)], v0, v1, v2);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_flat_triangle_common(&mut self,
+        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4, lines: [(f32, &glm::Vec4); 2],
+        v0: &V, v1: &V, v2: &V) {
+
+        let [(slope0, line_start0),
+            (slope1, line_start1)] = lines;
+
+        // The first integer row/column whose sample point (`c + offset`, under
+        // the active `SampleConvention`) is at or past the boundary `c`.
+        let pixel_center_offset = self.sample_convention.pixel_center_offset;
+        let snap = |c: f32| {
+            (c - pixel_center_offset).ceil()
+        };
+
+        let (scissor_x, scissor_y, scissor_width, scissor_height) = self.scissor
+            .unwrap_or((0, 0, self.target.size.0, self.target.size.1));
+        let scissor_x_end = scissor_x + scissor_width;
+        let scissor_y_end = scissor_y + scissor_height;
+
+        // `p0`/`p2` have already passed through `clip_polygon`, but a
+        // degenerate near-horizon triangle can still produce an edge slope
+        // large enough that naive extrapolation would overflow an `i32`
+        // row/column count. Clamp both ends of the range into the target
+        // (and scissor) bounds explicitly, rather than relying on only one
+        // side being bounded, so a stray huge coordinate can never turn into
+        // a loop spanning billions of rows or columns.
+        //
+        // Under `conservative`, pad by a row on each side: a triangle
+        // shorter than one pixel can otherwise fall entirely between two
+        // row centers and never reach the per-pixel coverage test at all,
+        // same as `x_start`/`x_end` already pad by a column below.
+        let row_pad = if self.conservative { 1.0 } else { 0.0 };
+        let y_start = (snap(p0.y) - row_pad).clamp(0.0, self.target.size.1 as f32).max(scissor_y as f32) as i32;
+        let y_end = (snap(p2.y) + row_pad).clamp(0.0, self.target.size.1 as f32).min(scissor_y_end as f32) as i32;
+        if y_start >= y_end {
+            return;
+        }
+
+        // Rows are disjoint, so every `y` can be shaded on its own thread: the
+        // raw pointers below are reconstructed per-row inside the closure and
+        // only ever used to touch that row's own pixels/depth samples.
+        let target_width = self.target.size.0;
+        let target_ptr = RowPtr(self.target.buffer.as_mut_ptr());
+        let bytes_per_pixel = self.target.bytes_per_pixel;
+        let depth_ptr = self.depth.as_mut().map(|(depth_buffer, depth_func)| {
+            (RowPtr(depth_buffer.buffer.as_mut_ptr() as *mut u8), *depth_func)
+        });
+        let stencil_ptr = self.stencil.as_mut().map(|(stencil_buffer, func, reference, op)| {
+            (RowPtr(stencil_buffer.buffer.as_mut_ptr()), *func, *reference, *op)
+        });
+        let pixel_shader = &self.pixel_shader;
+        let blend = self.blend;
+        let anti_alias = self.anti_alias;
+        let output_srgb = self.output_srgb;
+        let color_write = self.color_write;
+        let fog = self.fog;
+        let sample_convention = self.sample_convention;
+        let coverage_test = self.coverage_test;
+        let shade_model = self.shade_model;
+        let debug_output = self.debug_output;
+        let conservative = self.conservative;
+        let depth_range = self.depth_range;
+        let clip_plane = self.clip_plane;
+        let overdraw_ptr = self.overdraw.as_mut().map(|overdraw_buffer| {
+            RowPtr(overdraw_buffer.buffer.as_mut_ptr() as *mut u8)
+        });
+        let id_ptr = self.id_buffer.as_mut().map(|(id_buffer, id)| {
+            (RowPtr(id_buffer.buffer.as_mut_ptr() as *mut u8), *id)
+        });
+        let stats = &self.stats;
+        let bary_setup = BarycentricSetup::new(p0, p1, p2);
+        // Computed once outside the per-row/per-pixel loop below: `Flat`
+        // shades from the provoking vertex `v0` alone, so every covered
+        // pixel reuses this same result instead of re-shading per pixel.
+        // Skipped entirely under `Depth`/`Barycentric`, which replace the
+        // pixel shader's output rather than feeding it and so never call it.
+        let flat_color = match (shade_model, debug_output) {
+            (ShadeModel::Flat, DebugOutput::Normal | DebugOutput::Overdraw) => Some(pixel_shader.shade(v0)),
+            _ => None
+        };
+
+        (y_start..y_end).into_par_iter().for_each(|y| {
+            let px0 = slope0 * (y as f32 + pixel_center_offset - line_start0.y) + line_start0.x;
+            let px1 = slope1 * (y as f32 + pixel_center_offset - line_start1.y) + line_start1.x;
+
+            // The slopes above only give an approximate per-row span; widen it
+            // by a pixel on each side and let `covers_with_top_left_rule`
+            // decide exact coverage, so two triangles sharing this edge agree
+            // on which one owns a pixel exactly on the boundary.
+            let x_start = (snap(px0) - 1.0).clamp(0.0, target_width as f32).max(scissor_x as f32) as i32;
+            let x_end = (snap(px1) + 1.0).clamp(0.0, target_width as f32).min(scissor_x_end as f32) as i32;
+
+            #[cfg(not(feature = "simd_barycentric"))]
+            let (_, mut f1, mut f2) = bary_setup.weights_at(x_start as f32 + pixel_center_offset, y as f32 + pixel_center_offset);
+            #[cfg(not(feature = "simd_barycentric"))]
+            let (step1, step2) = bary_setup.step_x();
+            // Refilled every 4th pixel via `edges_batch4` instead of the
+            // one-at-a-time `weights_at`/`step_x` above.
+            #[cfg(feature = "simd_barycentric")]
+            let mut batch = [(0.0f32, 0.0f32, 0.0f32); 4];
+
+            for x in x_start..x_end {
+                #[cfg(feature = "simd_barycentric")]
+                let f = {
+                    let lane = (x - x_start).rem_euclid(4);
+                    if lane == 0 {
+                        batch = bary_setup.edges_batch4(x as f32 + pixel_center_offset, y as f32 + pixel_center_offset);
+                    }
+                    batch[lane as usize]
+                };
+                #[cfg(not(feature = "simd_barycentric"))]
+                let f = {
+                    let f = (1.0 - f1 - f2, f1, f2);
+                    f1 += step1;
+                    f2 += step2;
+                    f
+                };
+
+                let coverage = match anti_alias {
+                    AntiAlias::Coverage => Self::edge_coverage(p0, p1, p2, x, y, sample_convention),
+                    _ => {
+                        let inside = if conservative {
+                            Self::edge_coverage(p0, p1, p2, x, y, sample_convention) > 0.0
+                        } else {
+                            match coverage_test {
+                                CoverageTest::TopLeftRule => Self::covers_with_top_left_rule(p0, p1, p2, x, y, sample_convention),
+                                CoverageTest::BarycentricInside => BarycentricSetup::is_inside(f)
+                            }
+                        };
+                        if inside { 1.0 } else { 0.0 }
+                    }
+                };
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                if let Some(plane) = clip_plane {
+                    if Self::clip_plane_rejects(plane, &(*v0 * f.0 + *v1 * f.1 + *v2 * f.2)) {
+                        continue;
+                    }
+                }
+
+                // Counted here, before the stencil/depth tests below, so it
+                // reflects every fragment that survives rasterization
+                // regardless of whether either test then rejects it.
+                if let Some(overdraw_ptr) = overdraw_ptr {
+                    let overdraw_slot = unsafe {
+                        &mut *(overdraw_ptr.0 as *mut u16).add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    *overdraw_slot += 1;
+                }
+
+                if let Some((stencil_ptr, func, reference, op)) = stencil_ptr {
+                    let stencil_slot = unsafe {
+                        &mut *stencil_ptr.0.add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    if !func.passes(reference, *stencil_slot) {
+                        continue;
+                    }
+                    *stencil_slot = op.apply(*stencil_slot, reference);
+                }
+
+                let depth_value = p0.z * f.0 + p1.z * f.1 + p2.z * f.2;
+                let depth_slot = depth_ptr.map(|(depth_ptr, depth_func)| {
+                    let depth_slot = unsafe {
+                        &mut *(depth_ptr.0 as *mut f32).add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    (depth_slot, depth_value, depth_func)
+                });
+                if let Some((depth_slot, depth, depth_func)) = &depth_slot {
+                    if !depth_func.passes(*depth, **depth_slot) {
+                        stats.fragments_depth_rejected.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                }
+
+                if !color_write {
+                    if let Some((depth_slot, depth, _)) = depth_slot {
+                        *depth_slot = depth;
+                    }
+                    continue;
+                }
+
+                let color = match debug_output {
+                    DebugOutput::Normal | DebugOutput::Overdraw => match flat_color {
+                        Some(color) => color,
+                        None => pixel_shader.shade(&(*v0 * f.0 + *v1 * f.1 + *v2 * f.2))
+                    },
+                    DebugOutput::Depth { near, far } => Some(Self::debug_depth_color(depth_value, depth_range, near, far)),
+                    DebugOutput::Barycentric => Some(Self::debug_barycentric_color(f))
+                };
+                let Some(color) = color else {
+                    continue;
+                };
+                stats.fragments_shaded.fetch_add(1, Ordering::Relaxed);
+                if let Some((depth_slot, depth, _)) = depth_slot {
+                    *depth_slot = depth;
+                }
+                if let Some((id_ptr, id)) = id_ptr {
+                    let id_slot = unsafe {
+                        &mut *(id_ptr.0 as *mut u32).add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    *id_slot = id;
+                }
+                let color = match &fog {
+                    Some(fog) => fog.apply(color, depth_value),
+                    None => color
+                };
+                let color = if output_srgb {
+                    [Self::encode_srgb_channel(color[0]), Self::encode_srgb_channel(color[1]),
+                        Self::encode_srgb_channel(color[2]), color[3]]
+                } else {
+                    color
+                };
+                let index = (bytes_per_pixel * (y as u32 * target_width + x as u32)) as usize;
+                let pixel = unsafe { std::slice::from_raw_parts_mut(target_ptr.0.add(index), 4) };
+                let color = match blend {
+                    BlendMode::Opaque => color,
+                    _ => blend.blend(color, [pixel[0], pixel[1], pixel[2], pixel[3]])
+                };
+                let color = if coverage < 1.0 {
+                    let mut out = [0u8; 4];
+                    for i in 0..4 {
+                        out[i] = (color[i] as f32 * coverage + pixel[i] as f32 * (1.0 - coverage)).round() as u8;
+                    }
+                    out
+                } else {
+                    color
+                };
+                pixel.copy_from_slice(&color);
+            }
+        });
+    }
+
+    // Alternative to `draw_triangle`'s flat-top/flat-bottom scanline split:
+    // walks the triangle's integer bounding box directly and tests every
+    // pixel against all three edge functions via `covers_with_top_left_rule`,
+    // the same fill rule the scanline path's default `CoverageTest::TopLeftRule`
+    // uses, so the two backends agree pixel-for-pixel under that fill rule.
+    // Simpler (no y-sorting, no flat-top/flat-bottom split) and handles every
+    // triangle orientation the same way, at the cost of testing some pixels
+    // outside the triangle that scanline's per-row bounds already exclude.
+    // Selected via `RasterBackend::EdgeFunction`; anti-aliasing, `conservative`
+    // rasterization and `CoverageTest::BarycentricInside` are scanline-only.
+    fn draw_triangle_edge_function(&mut self,
+        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
+        v0: &V, v1: &V, v2: &V) {
+
+        const DEGENERATE_AREA_EPSILON: f32 = 1e-6;
+        let signed_area = (p1.x - p0.x) * (p2.y - p0.y) - (p2.x - p0.x) * (p1.y - p0.y);
+        if signed_area.abs() < DEGENERATE_AREA_EPSILON {
+            return;
+        }
+
+        let (scissor_x, scissor_y, scissor_width, scissor_height) = self.scissor
+            .unwrap_or((0, 0, self.target.size.0, self.target.size.1));
+        let scissor_x_end = scissor_x + scissor_width;
+        let scissor_y_end = scissor_y + scissor_height;
+
+        let x_min = p0.x.min(p1.x).min(p2.x).floor().max(0.0).max(scissor_x as f32) as i32;
+        let x_max = p0.x.max(p1.x).max(p2.x).ceil().min(self.target.size.0 as f32).min(scissor_x_end as f32) as i32;
+        let y_min = p0.y.min(p1.y).min(p2.y).floor().max(0.0).max(scissor_y as f32) as i32;
+        let y_max = p0.y.max(p1.y).max(p2.y).ceil().min(self.target.size.1 as f32).min(scissor_y_end as f32) as i32;
+        if x_min >= x_max || y_min >= y_max {
+            return;
+        }
+
+        let target_width = self.target.size.0;
+        let target_ptr = RowPtr(self.target.buffer.as_mut_ptr());
+        let bytes_per_pixel = self.target.bytes_per_pixel;
+        let depth_ptr = self.depth.as_mut().map(|(depth_buffer, depth_func)| {
+            (RowPtr(depth_buffer.buffer.as_mut_ptr() as *mut u8), *depth_func)
+        });
+        let stencil_ptr = self.stencil.as_mut().map(|(stencil_buffer, func, reference, op)| {
+            (RowPtr(stencil_buffer.buffer.as_mut_ptr()), *func, *reference, *op)
+        });
+        let pixel_shader = &self.pixel_shader;
+        let blend = self.blend;
+        let output_srgb = self.output_srgb;
+        let color_write = self.color_write;
+        let fog = self.fog;
+        let sample_convention = self.sample_convention;
+        let shade_model = self.shade_model;
+        let debug_output = self.debug_output;
+        let depth_range = self.depth_range;
+        let clip_plane = self.clip_plane;
+        let overdraw_ptr = self.overdraw.as_mut().map(|overdraw_buffer| {
+            RowPtr(overdraw_buffer.buffer.as_mut_ptr() as *mut u8)
+        });
+        let id_ptr = self.id_buffer.as_mut().map(|(id_buffer, id)| {
+            (RowPtr(id_buffer.buffer.as_mut_ptr() as *mut u8), *id)
+        });
+        let stats = &self.stats;
+        let bary_setup = BarycentricSetup::new(p0, p1, p2);
+        let flat_color = match (shade_model, debug_output) {
+            (ShadeModel::Flat, DebugOutput::Normal | DebugOutput::Overdraw) => Some(pixel_shader.shade(v0)),
+            _ => None
+        };
+
+        // Rows are disjoint, same as `draw_flat_triangle_common`: the raw
+        // pointers above are reconstructed per-row inside the closure and
+        // only ever used to touch that row's own pixels/depth samples.
+        (y_min..y_max).into_par_iter().for_each(|y| {
+            for x in x_min..x_max {
+                if !Self::covers_with_top_left_rule(p0, p1, p2, x, y, sample_convention) {
+                    continue;
+                }
+                let offset = sample_convention.pixel_center_offset;
+                let f = bary_setup.weights_at(x as f32 + offset, y as f32 + offset);
+
+                if let Some(plane) = clip_plane {
+                    if Self::clip_plane_rejects(plane, &(*v0 * f.0 + *v1 * f.1 + *v2 * f.2)) {
+                        continue;
+                    }
+                }
+
+                // Counted here, before the stencil/depth tests below, so it
+                // reflects every fragment that survives rasterization
+                // regardless of whether either test then rejects it.
+                if let Some(overdraw_ptr) = overdraw_ptr {
+                    let overdraw_slot = unsafe {
+                        &mut *(overdraw_ptr.0 as *mut u16).add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    *overdraw_slot += 1;
+                }
+
+                if let Some((stencil_ptr, func, reference, op)) = stencil_ptr {
+                    let stencil_slot = unsafe {
+                        &mut *stencil_ptr.0.add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    if !func.passes(reference, *stencil_slot) {
+                        continue;
+                    }
+                    *stencil_slot = op.apply(*stencil_slot, reference);
+                }
+
+                let depth_value = p0.z * f.0 + p1.z * f.1 + p2.z * f.2;
+                let depth_slot = depth_ptr.map(|(depth_ptr, depth_func)| {
+                    let depth_slot = unsafe {
+                        &mut *(depth_ptr.0 as *mut f32).add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    (depth_slot, depth_value, depth_func)
+                });
+                if let Some((depth_slot, depth, depth_func)) = &depth_slot {
+                    if !depth_func.passes(*depth, **depth_slot) {
+                        stats.fragments_depth_rejected.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                }
+
+                if !color_write {
+                    if let Some((depth_slot, depth, _)) = depth_slot {
+                        *depth_slot = depth;
+                    }
+                    continue;
+                }
+
+                let color = match debug_output {
+                    DebugOutput::Normal | DebugOutput::Overdraw => match flat_color {
+                        Some(color) => color,
+                        None => pixel_shader.shade(&(*v0 * f.0 + *v1 * f.1 + *v2 * f.2))
+                    },
+                    DebugOutput::Depth { near, far } => Some(Self::debug_depth_color(depth_value, depth_range, near, far)),
+                    DebugOutput::Barycentric => Some(Self::debug_barycentric_color(f))
+                };
+                let Some(color) = color else {
+                    continue;
+                };
+                stats.fragments_shaded.fetch_add(1, Ordering::Relaxed);
+                if let Some((depth_slot, depth, _)) = depth_slot {
+                    *depth_slot = depth;
+                }
+                if let Some((id_ptr, id)) = id_ptr {
+                    let id_slot = unsafe {
+                        &mut *(id_ptr.0 as *mut u32).add((y as u32 * target_width + x as u32) as usize)
+                    };
+                    *id_slot = id;
+                }
+                let color = match &fog {
+                    Some(fog) => fog.apply(color, depth_value),
+                    None => color
+                };
+                let color = if output_srgb {
+                    [Self::encode_srgb_channel(color[0]), Self::encode_srgb_channel(color[1]),
+                        Self::encode_srgb_channel(color[2]), color[3]]
+                } else {
+                    color
+                };
+                let index = (bytes_per_pixel * (y as u32 * target_width + x as u32)) as usize;
+                let pixel = unsafe { std::slice::from_raw_parts_mut(target_ptr.0.add(index), 4) };
+                let color = match blend {
+                    BlendMode::Opaque => color,
+                    _ => blend.blend(color, [pixel[0], pixel[1], pixel[2], pixel[3]])
+                };
+                pixel.copy_from_slice(&color);
+            }
+        });
+    }
+
+    // Recomputes barycentric weights from scratch via dot products, the way
+    // this codebase did before incremental edge-function setup (see
+    // `BarycentricSetup`) replaced it in the hot raster loops. Kept around
+    // under the `legacy-barycentric` feature as a reference implementation
+    // and a cross-check in tests.
+    #[cfg(all(test, feature = "legacy-barycentric"))]
+    fn barycentric_coordinates_from_scratch(p: &glm::Vec4, p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4) -> (f32, f32, f32) {
+        let v0 = p1 - p0;
+        let v1 = p2 - p0;
+        let v2 = p - p0;
+        let d00 = glm::dot(&v0.xy(), &v0.xy());
+        let d01 = glm::dot(&v0.xy(), &v1.xy());
+        let d11 = glm::dot(&v1.xy(), &v1.xy());
+        let d20 = glm::dot(&v2.xy(), &v0.xy());
+        let d21 = glm::dot(&v2.xy(), &v1.xy());
+        let denom = d00 * d11 - d01 * d01;
+        let f1 = (d11 * d20 - d01 * d21) / denom;
+        let f2 = (d00 * d21 - d01 * d20) / denom;
+        let f0 = 1.0 - f1 - f2;
+        (f0, f1, f2)
+    }
+
+    fn edge_function(a: &glm::Vec4, b: &glm::Vec4, px: f32, py: f32) -> f32 {
+        (px - a.x) * (b.y - a.y) - (py - a.y) * (b.x - a.x)
+    }
+
+    // Undoes the perspective projection's nonlinear depth distribution,
+    // turning an NDC z back into a view-space distance between `near` and
+    // `far`.
+    fn linearize_depth(ndc_z: f32, near: f32, far: f32) -> f32 {
+        (2.0 * near * far) / (far + near - ndc_z * (far - near))
+    }
+
+    // `DebugOutput::Depth`: white at `near`, black at `far`.
+    fn debug_depth_color(depth_value: f32, depth_range: DepthRange, near: f32, far: f32) -> [u8; 4] {
+        let ndc_z = match depth_range {
+            DepthRange::NegativeOneToOne => depth_value,
+            DepthRange::ZeroToOne => depth_value * 2.0 - 1.0,
+            DepthRange::ReverseZeroToOne => 1.0 - depth_value * 2.0
+        };
+        let linear = Self::linearize_depth(ndc_z, near, far);
+        let normalized = (1.0 - (linear - near) / (far - near)).clamp(0.0, 1.0);
+        let value = (normalized * 255.0).round() as u8;
+        [value, value, value, 255]
+    }
+
+    // `DebugOutput::Barycentric`: each fragment's weights drawn directly as RGB.
+    fn debug_barycentric_color(f: (f32, f32, f32)) -> [u8; 4] {
+        [(f.0.clamp(0.0, 1.0) * 255.0) as u8, (f.1.clamp(0.0, 1.0) * 255.0) as u8, (f.2.clamp(0.0, 1.0) * 255.0) as u8, 255]
+    }
+
+    // True when a fragment interpolated to `v` lies on the negative side of
+    // `with_clip_plane`'s plane and should be discarded.
+    fn clip_plane_rejects(plane: glm::Vec4, v: &V) -> bool {
+        let p = v.position();
+        plane.x * p.x + plane.y * p.y + plane.z * p.z + plane.w < 0.0
+    }
+
+
+    // A "top" edge is horizontal and points leftward, a "left" edge points
+    // downward (in screen space, where y grows downward); a pixel lying
+    // exactly on a top or left edge is included, on any other edge it is
+    // excluded. This is the standard top-left fill rule: it gives each
+    // shared edge between two adjacent triangles to exactly one of them.
+    fn is_top_left_edge(a: &glm::Vec4, b: &glm::Vec4) -> bool {
+        let dx = b.x - a.x;
+        let dy = b.y - a.y;
+        (dy == 0.0 && dx < 0.0) || dy > 0.0
+    }
+
+    fn covers_with_top_left_rule(p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4, x: i32, y: i32, sample_convention: SampleConvention) -> bool {
+        let (px, py) = (x as f32 + sample_convention.pixel_center_offset, y as f32 + sample_convention.pixel_center_offset);
+
+        let mut e0 = Self::edge_function(p1, p2, px, py);
+        let mut e1 = Self::edge_function(p2, p0, px, py);
+        let mut e2 = Self::edge_function(p0, p1, px, py);
+        let mut area = Self::edge_function(p0, p1, p2.x, p2.y);
+
+        // Normalize to counter-clockwise winding so the top-left rule below
+        // can assume a consistent orientation; flipping the edge direction
+        // along with its value keeps "is this the triangle's top or left
+        // edge" correct for a triangle that was wound clockwise.
+        let clockwise = area < 0.0;
+        if clockwise {
+            e0 = -e0;
+            e1 = -e1;
+            e2 = -e2;
+            area = -area;
+        }
+        if area == 0.0 {
+            return false;
+        }
+
+        let covered = |e: f32, a: &glm::Vec4, b: &glm::Vec4| {
+            let (a, b) = if clockwise { (b, a) } else { (a, b) };
+            e > 0.0 || (e == 0.0 && Self::is_top_left_edge(a, b))
+        };
+
+        covered(e0, p1, p2) && covered(e1, p2, p0) && covered(e2, p0, p1)
+    }
+
+    // Non-mutating: walks the triangle's bounding box (clamped to
+    // `target_size`) and yields every pixel `covers_with_top_left_rule`
+    // says is inside, paired with its barycentric-interpolated vertex.
+    // Never touches a framebuffer or calls the pixel shader, so it's
+    // useful for tests that want to assert exact coverage, or for custom
+    // compositing that drives its own write logic. `draw_flat_triangle_common`
+    // isn't rebuilt on top of this: it needs per-row parallelism plus
+    // depth/stencil/blend integration that a flat serial iterator over
+    // the whole triangle would give up.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_triangle(p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4, v0: &V, v1: &V, v2: &V, target_size: (u32, u32), sample_convention: SampleConvention) -> impl Iterator<Item = (u32, u32, V)> {
+        let bary_setup = BarycentricSetup::new(p0, p1, p2);
+        let x_min = p0.x.min(p1.x).min(p2.x).floor().max(0.0) as i32;
+        let x_max = p0.x.max(p1.x).max(p2.x).ceil().min(target_size.0 as f32) as i32;
+        let y_min = p0.y.min(p1.y).min(p2.y).floor().max(0.0) as i32;
+        let y_max = p0.y.max(p1.y).max(p2.y).ceil().min(target_size.1 as f32) as i32;
+        let (p0, p1, p2) = (*p0, *p1, *p2);
+        let (v0, v1, v2) = (*v0, *v1, *v2);
+        let offset = sample_convention.pixel_center_offset;
+
+        (y_min..y_max).flat_map(move |y| (x_min..x_max).map(move |x| (x, y)))
+            .filter(move |&(x, y)| Self::covers_with_top_left_rule(&p0, &p1, &p2, x, y, sample_convention))
+            .map(move |(x, y)| {
+                let (f0, f1, f2) = bary_setup.weights_at(x as f32 + offset, y as f32 + offset);
+                (x as u32, y as u32, v0 * f0 + v1 * f1 + v2 * f2)
+            })
+    }
+
+    // Fractional coverage of the pixel at `(x, y)` by the triangle, based on
+    // each edge's signed distance to the pixel in pixel units: a pixel whose
+    // center sits exactly on an edge is 50% covered, one half a pixel inside
+    // is fully covered, and one half a pixel outside is not covered at all.
+    // Coverage is the minimum across the three edges, so it only softens a
+    // triangle's own silhouette; a pixel deep inside relative to one edge but
+    // near another (e.g. near a shared interior edge) is still softened.
+    fn edge_coverage(p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4, x: i32, y: i32, sample_convention: SampleConvention) -> f32 {
+        let (px, py) = (x as f32 + sample_convention.pixel_center_offset, y as f32 + sample_convention.pixel_center_offset);
+
+        let mut e0 = Self::edge_function(p1, p2, px, py);
+        let mut e1 = Self::edge_function(p2, p0, px, py);
+        let mut e2 = Self::edge_function(p0, p1, px, py);
+        let mut area = Self::edge_function(p0, p1, p2.x, p2.y);
+
+        if area < 0.0 {
+            e0 = -e0;
+            e1 = -e1;
+            e2 = -e2;
+            area = -area;
+        }
+        if area == 0.0 {
+            return 0.0;
+        }
+
+        let edge_length = |a: &glm::Vec4, b: &glm::Vec4| ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt();
+        let coverage = |e: f32, a: &glm::Vec4, b: &glm::Vec4| (e / edge_length(a, b) + 0.5).clamp(0.0, 1.0);
+
+        coverage(e0, p1, p2).min(coverage(e1, p2, p0)).min(coverage(e2, p0, p1))
+    }
+
+    // Standard sRGB transfer curve (a linear segment near black, then a gamma
+    // ~2.4 power curve), not a plain 2.2 power approximation.
+    fn encode_srgb_channel(byte: u8) -> u8 {
+        let linear = byte as f32 / 255.0;
+        let encoded = if linear <= 0.0031308 {
+            linear * 12.92
+        } else {
+            1.055 * linear.powf(1.0 / 2.4) - 0.055
+        };
+        (encoded.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    fn transform_to_target_coordinates(&self, v: &glm::Vec4) -> glm::Vec4 {
+        let z = match self.depth_range {
+            DepthRange::NegativeOneToOne => v.z,
+            DepthRange::ZeroToOne => (v.z + 1.0) * 0.5,
+            DepthRange::ReverseZeroToOne => (1.0 - v.z) * 0.5
+        };
+        glm::vec4(
+            (v.x + 1.0) * (self.viewport.width as f32 / 2.0) + self.viewport.x as f32,
+            (v.y + 1.0) * (self.viewport.height as f32 / 2.0) + self.viewport.y as f32,
+            z,
+            v.w
+        )
+    }
+
+}
+
+// Builds a flat, densely subdivided quad as `2 * subdivisions^2` triangles,
+// used to benchmark the rasterizer's scanline throughput independently of
+// the demo's camera or cube geometry.
+pub fn generate_dense_grid_mesh(subdivisions: u32) -> (Vec<Vertex>, Vec<usize>) {
+    let mut vertices = Vec::with_capacity(((subdivisions + 1) * (subdivisions + 1)) as usize);
+    for y in 0..=subdivisions {
+        for x in 0..=subdivisions {
+            let u = x as f32 / subdivisions as f32;
+            let v = y as f32 / subdivisions as f32;
+            vertices.push(Vertex {
+                position: glm::vec3(u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0),
+                uv: glm::vec2(u, v),
+                normal: glm::vec3(0.0, 0.0, 1.0)
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((subdivisions * subdivisions * 6) as usize);
+    let row = subdivisions + 1;
+    for y in 0..subdivisions {
+        for x in 0..subdivisions {
+            let top_left = (y * row + x) as usize;
+            let top_right = top_left + 1;
+            let bottom_left = ((y + 1) * row + x) as usize;
+            let bottom_right = bottom_left + 1;
+            indices.extend_from_slice(&[top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+/// Builds a unit-radius UV sphere via lat/long tessellation, with the vertex
+/// normal equal to its own (already unit-length) position. Kept low-poly by
+/// the caller to make the Gouraud-vs-Phong banding comparison visible.
+pub fn generate_sphere_mesh(lat_segments: u32, lon_segments: u32) -> (Vec<Vertex>, Vec<usize>) {
+    let mut vertices = Vec::with_capacity(((lat_segments + 1) * (lon_segments + 1)) as usize);
+    for lat in 0..=lat_segments {
+        let theta = std::f32::consts::PI * lat as f32 / lat_segments as f32;
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        for lon in 0..=lon_segments {
+            let phi = 2.0 * std::f32::consts::PI * lon as f32 / lon_segments as f32;
+            let (sin_phi, cos_phi) = phi.sin_cos();
+            let normal = glm::vec3(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi);
+            vertices.push(Vertex {
+                position: normal,
+                uv: glm::vec2(lon as f32 / lon_segments as f32, lat as f32 / lat_segments as f32),
+                normal
+            });
+        }
+    }
+
+    let mut indices = Vec::with_capacity((lat_segments * lon_segments * 6) as usize);
+    let row = lon_segments + 1;
+    for lat in 0..lat_segments {
+        for lon in 0..lon_segments {
+            let top_left = (lat * row + lon) as usize;
+            let top_right = top_left + 1;
+            let bottom_left = ((lat + 1) * row + lon) as usize;
+            let bottom_right = bottom_left + 1;
+            indices.extend_from_slice(&[top_left, bottom_left, top_right, top_right, bottom_left, bottom_right]);
+        }
+    }
+
+    (vertices, indices)
+}
+
+// Wireframe geometry for an XZ-plane grid centered at the origin: a line
+// every `spacing` units out to `extent` on each axis, meant for spatial
+// orientation in examples and debug views rather than production geometry.
+pub fn generate_grid_lines(spacing: f32, extent: f32) -> (Vec<Vertex>, Vec<usize>) {
+    let steps = (extent / spacing).floor() as i32;
+    let line_count = (2 * steps + 1) as usize * 2;
+    let mut vertices = Vec::with_capacity(line_count * 2);
+    let mut indices = Vec::with_capacity(line_count * 2);
+
+    let push_line = |a: glm::Vec3, b: glm::Vec3, vertices: &mut Vec<Vertex>, indices: &mut Vec<usize>| {
+        let base = vertices.len();
+        vertices.push(Vertex { position: a, uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 1.0, 0.0) });
+        vertices.push(Vertex { position: b, uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 1.0, 0.0) });
+        indices.push(base);
+        indices.push(base + 1);
+    };
+
+    for i in -steps..=steps {
+        let offset = i as f32 * spacing;
+        push_line(glm::vec3(offset, 0.0, -extent), glm::vec3(offset, 0.0, extent), &mut vertices, &mut indices);
+        push_line(glm::vec3(-extent, 0.0, offset), glm::vec3(extent, 0.0, offset), &mut vertices, &mut indices);
+    }
+
+    (vertices, indices)
+}
+
+// Renders an XZ-plane grid (e.g. a "floor" under a rotating model) for
+// spatial orientation. `vertex_shader` should apply the same MVP transform
+// as the rest of the scene; the grid gets its own `RenderContext` because a
+// flat `color` can't be threaded through an already-built context's fixed
+// pixel shader.
+pub fn draw_grid<VS: Fn(&mut Vertex) -> glm::Vec4>(
+    target: &mut TextureBuffer<'_>, depth: Option<&mut DepthBuffer>,
+    vertex_shader: VS, spacing: f32, extent: f32, color: [u8; 4]) {
+
+    let (vertices, indices) = generate_grid_lines(spacing, extent);
+    let mut render_context = RenderContext::new(CullMode::None, target, vertex_shader, move |_: &Vertex| color);
+    if let Some(depth) = depth {
+        render_context = render_context.with_depth_test(depth, DepthFunc::Less);
+    }
+    render_context.draw_indexed_lines(&indices, &vertices, LineTopology::LineList);
+}
+
+// Wireframe geometry for visualizing per-vertex normals: one line segment
+// per vertex, from `position` to `position + normal * length`. Meant for
+// verifying `compute_smooth_normals`/`compute_tangents` output rather than
+// production geometry.
+pub fn generate_normal_lines(vertices: &[Vertex], length: f32) -> (Vec<Vertex>, Vec<usize>) {
+    let mut out_vertices = Vec::with_capacity(vertices.len() * 2);
+    let mut indices = Vec::with_capacity(vertices.len() * 2);
+
+    for vertex in vertices {
+        let base = out_vertices.len();
+        out_vertices.push(Vertex { position: vertex.position, uv: vertex.uv, normal: vertex.normal });
+        out_vertices.push(Vertex { position: vertex.position + vertex.normal * length, uv: vertex.uv, normal: vertex.normal });
+        indices.push(base);
+        indices.push(base + 1);
+    }
+
+    (out_vertices, indices)
+}
+
+// Renders a short line per vertex from `position` out along `normal`, for
+// verifying normals visually after `compute_smooth_normals`. Gets its own
+// `RenderContext` for the same reason `draw_grid` does: a flat `color`
+// can't be threaded through an already-built context's fixed pixel shader.
+pub fn draw_normals<VS: Fn(&mut Vertex) -> glm::Vec4>(
+    target: &mut TextureBuffer<'_>, depth: Option<&mut DepthBuffer>,
+    vertex_shader: VS, vertices: &[Vertex], length: f32, color: [u8; 4]) {
+
+    let (line_vertices, indices) = generate_normal_lines(vertices, length);
+    let mut render_context = RenderContext::new(CullMode::None, target, vertex_shader, move |_: &Vertex| color);
+    if let Some(depth) = depth {
+        render_context = render_context.with_depth_test(depth, DepthFunc::Less);
+    }
+    render_context.draw_indexed_lines(&indices, &line_vertices, LineTopology::LineList);
+}
+
+// A leaner sibling of `RenderContext` for writing several color attachments
+// (e.g. albedo, normal, position for deferred shading) in one rasterization
+// pass. `PixelShader`'s `[u8; 4]` return type can't grow into `[[u8; 4]; N]`
+// without breaking every existing shader, so this is offered as its own type
+// rather than a `RenderContext` mode. Deliberately narrow scope to match: no
+// depth/stencil test, no blending, no antialiasing, no clipping against the
+// near plane, no tiled backend — just vertex shade, perspective divide,
+// screen-space backface cull, and a per-pixel `BarycentricSetup::is_inside`
+// test writing straight into all `N` targets, mirroring `rasterize_triangle`'s
+// own "simpler, does-less" precedent. All `N` targets must share `target_size`.
+pub struct MrtRenderContext<'a, 'b, V: Clone + Linear, VS: Fn(&mut V) -> glm::Vec4,
+    PS: Fn(&V) -> [[u8; 4]; N] + Sync, const N: usize> {
+    cull_mode: CullMode,
+    targets: [&'a mut TextureBuffer<'b>; N],
+    vertex_shader: VS,
+    pixel_shader: PS,
+    phantom: PhantomData<V>
+}
+
+impl<'a, 'b, V: Clone + Linear, VS: Fn(&mut V) -> glm::Vec4,
+    PS: Fn(&V) -> [[u8; 4]; N] + Sync, const N: usize> MrtRenderContext<'a, 'b, V, VS, PS, N> {
+    pub fn new(cull_mode: CullMode, targets: [&'a mut TextureBuffer<'b>; N], vertex_shader: VS, pixel_shader: PS) -> Self {
+        MrtRenderContext { cull_mode, targets, vertex_shader, pixel_shader, phantom: PhantomData }
+    }
+
+    pub fn draw_indexed_triangles(&mut self, indices: &[usize], vertices: &[V]) {
+        let target_size = self.targets[0].size;
+        let mut shaded_vertices = vertices.to_vec();
+        let vertex_shader = &self.vertex_shader;
+        let positions: Vec<glm::Vec4> = shaded_vertices.iter_mut().map(vertex_shader).collect();
+
+        for triangle in indices.chunks_exact(3) {
+            let (i0, i1, i2) = (triangle[0], triangle[1], triangle[2]);
+            let mut p0 = positions[i0] / positions[i0].w;
+            let mut p1 = positions[i1] / positions[i1].w;
+            let mut p2 = positions[i2] / positions[i2].w;
+
+            if self.cull_mode != CullMode::None {
+                let d0 = p2 - p0;
+                let d1 = p2 - p1;
+                let facing_sign = (d0.x * d1.y) - (d0.y * d1.x);
+                let is_front_facing = facing_sign >= 0.0;
+                let should_cull = match self.cull_mode {
+                    CullMode::None => false,
+                    CullMode::Front => is_front_facing,
+                    CullMode::Back => !is_front_facing
+                };
+                if should_cull {
+                    continue;
+                }
+            }
+
+            let to_screen = |p: glm::Vec4| glm::vec4(
+                (p.x + 1.0) * (target_size.0 as f32 / 2.0),
+                (p.y + 1.0) * (target_size.1 as f32 / 2.0),
+                p.z, p.w
+            );
+            p0 = to_screen(p0);
+            p1 = to_screen(p1);
+            p2 = to_screen(p2);
+
+            let bary_setup = BarycentricSetup::new(&p0, &p1, &p2);
+            let x_min = p0.x.min(p1.x).min(p2.x).floor().max(0.0) as i32;
+            let x_max = p0.x.max(p1.x).max(p2.x).ceil().min(target_size.0 as f32) as i32;
+            let y_min = p0.y.min(p1.y).min(p2.y).floor().max(0.0) as i32;
+            let y_max = p0.y.max(p1.y).max(p2.y).ceil().min(target_size.1 as f32) as i32;
+            let (v0, v1, v2) = (shaded_vertices[i0], shaded_vertices[i1], shaded_vertices[i2]);
+
+            for y in y_min..y_max {
+                for x in x_min..x_max {
+                    let weights = bary_setup.weights_at(x as f32, y as f32);
+                    if !BarycentricSetup::is_inside(weights) {
+                        continue;
+                    }
+                    let (f0, f1, f2) = weights;
+                    let vertex = v0 * f0 + v1 * f1 + v2 * f2;
+                    let outputs = (self.pixel_shader)(&vertex);
+                    for (target, color) in self.targets.iter_mut().zip(outputs) {
+                        target.set((x as u32, y as u32), &color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Bundles the finite-difference screen-space derivatives of an interpolated
+// vertex attribute across a 2x2 quad — the CPU analog of HLSL/GLSL's
+// `ddx`/`ddy` — so a pixel shader can answer "how fast is this attribute
+// changing on screen" (mipmap LOD selection, analytic antialiasing,
+// procedural texture footprints) without recomputing barycentric weights
+// itself. `ddx`/`ddy` are "coarse" derivatives shared by both fragments in
+// the same row/column of the quad, matching how GPUs compute them.
+#[derive(Clone, Copy)]
+pub struct Derivatives<V> {
+    pub ddx: V,
+    pub ddy: V
+}
+
+// Abstracts over pixel shaders that also receive per-fragment derivatives,
+// mirroring `PixelShader`. A separate trait rather than an extra method on
+// `PixelShader`, since the extra parameter is a breaking signature change
+// every existing shader would otherwise have to adopt.
+pub trait QuadPixelShader<V> {
+    fn shade(&self, vertex: &V, derivatives: &Derivatives<V>) -> Option<[u8; 4]>;
+}
+
+impl<V, F: Fn(&V, &Derivatives<V>) -> [u8; 4]> QuadPixelShader<V> for F {
+    fn shade(&self, vertex: &V, derivatives: &Derivatives<V>) -> Option<[u8; 4]> {
+        Some(self(vertex, derivatives))
+    }
+}
+
+// A leaner sibling of `RenderContext`, offered separately for the same
+// reason `MrtRenderContext` is: `QuadPixelShader`'s extra `Derivatives`
+// parameter is a breaking change to the pixel shader signature. Shades in
+// 2x2 quads instead of individual pixels: every quad interpolates its vertex
+// attribute at all four lattice points, even ones outside the triangle (the
+// same "helper invocation" trick GPUs use), so `ddx`/`ddy` stay defined right
+// up to a triangle's edge; only the points `BarycentricSetup::is_inside`
+// actually covers are shaded and written. No depth/stencil test, no
+// blending, no antialiasing, no clipping against the near plane, no tiled
+// backend.
+pub struct QuadRenderContext<'a, 'b, V: Clone + Linear, VS: Fn(&mut V) -> glm::Vec4,
+    PS: QuadPixelShader<V> + Sync> {
+    cull_mode: CullMode,
+    target: &'a mut TextureBuffer<'b>,
+    vertex_shader: VS,
+    pixel_shader: PS,
+    phantom: PhantomData<V>
+}
+
+impl<'a, 'b, V: Clone + Linear, VS: Fn(&mut V) -> glm::Vec4,
+    PS: QuadPixelShader<V> + Sync> QuadRenderContext<'a, 'b, V, VS, PS> {
+    pub fn new(cull_mode: CullMode, target: &'a mut TextureBuffer<'b>, vertex_shader: VS, pixel_shader: PS) -> Self {
+        QuadRenderContext { cull_mode, target, vertex_shader, pixel_shader, phantom: PhantomData }
+    }
+
+    pub fn draw_indexed_triangles(&mut self, indices: &[usize], vertices: &[V]) {
+        let target_size = self.target.size;
+        let mut shaded_vertices = vertices.to_vec();
+        let vertex_shader = &self.vertex_shader;
+        let positions: Vec<glm::Vec4> = shaded_vertices.iter_mut().map(vertex_shader).collect();
+
+        for triangle in indices.chunks_exact(3) {
+            let (i0, i1, i2) = (triangle[0], triangle[1], triangle[2]);
+            let mut p0 = positions[i0] / positions[i0].w;
+            let mut p1 = positions[i1] / positions[i1].w;
+            let mut p2 = positions[i2] / positions[i2].w;
+
+            if self.cull_mode != CullMode::None {
+                let d0 = p2 - p0;
+                let d1 = p2 - p1;
+                let facing_sign = (d0.x * d1.y) - (d0.y * d1.x);
+                let is_front_facing = facing_sign >= 0.0;
+                let should_cull = match self.cull_mode {
+                    CullMode::None => false,
+                    CullMode::Front => is_front_facing,
+                    CullMode::Back => !is_front_facing
+                };
+                if should_cull {
+                    continue;
+                }
+            }
+
+            let to_screen = |p: glm::Vec4| glm::vec4(
+                (p.x + 1.0) * (target_size.0 as f32 / 2.0),
+                (p.y + 1.0) * (target_size.1 as f32 / 2.0),
+                p.z, p.w
+            );
+            p0 = to_screen(p0);
+            p1 = to_screen(p1);
+            p2 = to_screen(p2);
+
+            let bary_setup = BarycentricSetup::new(&p0, &p1, &p2);
+            // Snapped down/up to even coordinates so every quad this loop
+            // visits starts on a `(2k, 2l)` lattice point, the same quad
+            // grid every other triangle in the draw call aligns to.
+            let x_min = (p0.x.min(p1.x).min(p2.x).floor().max(0.0) as i32) & !1;
+            let x_max = ((p0.x.max(p1.x).max(p2.x).ceil().min(target_size.0 as f32) as i32) + 1) & !1;
+            let y_min = (p0.y.min(p1.y).min(p2.y).floor().max(0.0) as i32) & !1;
+            let y_max = ((p0.y.max(p1.y).max(p2.y).ceil().min(target_size.1 as f32) as i32) + 1) & !1;
+            let (v0, v1, v2) = (shaded_vertices[i0], shaded_vertices[i1], shaded_vertices[i2]);
+
+            let attribute_at = |x: i32, y: i32| {
+                let (f0, f1, f2) = bary_setup.weights_at(x as f32, y as f32);
+                v0 * f0 + v1 * f1 + v2 * f2
+            };
+            let is_covered = |x: i32, y: i32| {
+                x >= 0 && y >= 0 && (x as u32) < target_size.0 && (y as u32) < target_size.1 &&
+                    BarycentricSetup::is_inside(bary_setup.weights_at(x as f32, y as f32))
+            };
+
+            let mut y = y_min;
+            while y < y_max {
+                let mut x = x_min;
+                while x < x_max {
+                    let top_left = attribute_at(x, y);
+                    let top_right = attribute_at(x + 1, y);
+                    let bottom_left = attribute_at(x, y + 1);
+                    let bottom_right = attribute_at(x + 1, y + 1);
+                    let top_ddx = top_right - top_left;
+                    let bottom_ddx = bottom_right - bottom_left;
+                    let left_ddy = bottom_left - top_left;
+                    let right_ddy = bottom_right - top_right;
+                    let quad = [
+                        (x, y, top_left, top_ddx, left_ddy),
+                        (x + 1, y, top_right, top_ddx, right_ddy),
+                        (x, y + 1, bottom_left, bottom_ddx, left_ddy),
+                        (x + 1, y + 1, bottom_right, bottom_ddx, right_ddy)
+                    ];
+                    for (px, py, vertex, ddx, ddy) in quad {
+                        if is_covered(px, py) {
+                            if let Some(color) = self.pixel_shader.shade(&vertex, &Derivatives { ddx, ddy }) {
+                                self.target.set((px as u32, py as u32), &color);
+                            }
+                        }
+                    }
+                    x += 2;
+                }
+                y += 2;
+            }
+        }
+    }
+}
+
+// Abstracts over vertex shaders that take a per-draw `Uniforms` value
+// alongside the vertex, mirroring `VertexShader` but for
+// `MaterialRenderContext`. A separate trait rather than an extra parameter
+// on `VertexShader`, since that would be a breaking signature change to
+// every existing vertex shader.
+pub trait UniformVertexShader<V, U> {
+    fn shade(&self, uniforms: &U, vertex: &mut V) -> glm::Vec4;
+}
+
+impl<V, U, F: Fn(&U, &mut V) -> glm::Vec4> UniformVertexShader<V, U> for F {
+    fn shade(&self, uniforms: &U, vertex: &mut V) -> glm::Vec4 {
+        self(uniforms, vertex)
+    }
+}
+
+// Mirrors `UniformVertexShader` for pixel shading.
+pub trait UniformPixelShader<V, U> {
+    fn shade(&self, uniforms: &U, vertex: &V) -> [u8; 4];
+}
+
+impl<V, U, F: Fn(&U, &V) -> [u8; 4]> UniformPixelShader<V, U> for F {
+    fn shade(&self, uniforms: &U, vertex: &V) -> [u8; 4] {
+        self(uniforms, vertex)
+    }
+}
+
+// A leaner sibling of `RenderContext`, offered separately for the same
+// reason `MrtRenderContext`/`QuadRenderContext` are: threading a `Uniforms`
+// value through the shader signature is a breaking change every existing
+// `VertexShader`/`PixelShader` closure would otherwise have to adopt.
+// `vertex_shader`/`pixel_shader` here take `&U` fresh from each
+// `draw_indexed_triangles` call instead of capturing per-draw state (an MVP
+// matrix, a bound texture, a tint) at construction time, so one long-lived
+// context can be reused across frames and materials without rebuilding it
+// just to swap what a closure captured. Deliberately narrow scope to match:
+// no depth/stencil test, no blending, no antialiasing, no clipping against
+// the near plane, no tiled backend.
+pub struct MaterialRenderContext<'a, 'b, V: Clone + Linear, U,
+    VS: UniformVertexShader<V, U>,
+    PS: UniformPixelShader<V, U> + Sync> {
+    cull_mode: CullMode,
+    target: &'a mut TextureBuffer<'b>,
+    vertex_shader: VS,
+    pixel_shader: PS,
+    phantom: PhantomData<(V, U)>
+}
+
+impl<'a, 'b, V: Clone + Linear, U,
+    VS: UniformVertexShader<V, U>,
+    PS: UniformPixelShader<V, U> + Sync> MaterialRenderContext<'a, 'b, V, U, VS, PS> {
+    pub fn new(cull_mode: CullMode, target: &'a mut TextureBuffer<'b>, vertex_shader: VS, pixel_shader: PS) -> Self {
+        MaterialRenderContext { cull_mode, target, vertex_shader, pixel_shader, phantom: PhantomData }
+    }
+
+    pub fn draw_indexed_triangles(&mut self, indices: &[usize], vertices: &[V], uniforms: &U) {
+        let target_size = self.target.size;
+        let mut shaded_vertices = vertices.to_vec();
+        let vertex_shader = &self.vertex_shader;
+        let positions: Vec<glm::Vec4> = shaded_vertices.iter_mut()
+            .map(|v| vertex_shader.shade(uniforms, v))
+            .collect();
+
+        for triangle in indices.chunks_exact(3) {
+            let (i0, i1, i2) = (triangle[0], triangle[1], triangle[2]);
+            let mut p0 = positions[i0] / positions[i0].w;
+            let mut p1 = positions[i1] / positions[i1].w;
+            let mut p2 = positions[i2] / positions[i2].w;
+
+            if self.cull_mode != CullMode::None {
+                let d0 = p2 - p0;
+                let d1 = p2 - p1;
+                let facing_sign = (d0.x * d1.y) - (d0.y * d1.x);
+                let is_front_facing = facing_sign >= 0.0;
+                let should_cull = match self.cull_mode {
+                    CullMode::None => false,
+                    CullMode::Front => is_front_facing,
+                    CullMode::Back => !is_front_facing
+                };
+                if should_cull {
+                    continue;
+                }
+            }
+
+            let to_screen = |p: glm::Vec4| glm::vec4(
+                (p.x + 1.0) * (target_size.0 as f32 / 2.0),
+                (p.y + 1.0) * (target_size.1 as f32 / 2.0),
+                p.z, p.w
+            );
+            p0 = to_screen(p0);
+            p1 = to_screen(p1);
+            p2 = to_screen(p2);
+
+            let bary_setup = BarycentricSetup::new(&p0, &p1, &p2);
+            let x_min = p0.x.min(p1.x).min(p2.x).floor().max(0.0) as i32;
+            let x_max = p0.x.max(p1.x).max(p2.x).ceil().min(target_size.0 as f32) as i32;
+            let y_min = p0.y.min(p1.y).min(p2.y).floor().max(0.0) as i32;
+            let y_max = p0.y.max(p1.y).max(p2.y).ceil().min(target_size.1 as f32) as i32;
+            let (v0, v1, v2) = (shaded_vertices[i0], shaded_vertices[i1], shaded_vertices[i2]);
+
+            for y in y_min..y_max {
+                for x in x_min..x_max {
+                    let weights = bary_setup.weights_at(x as f32, y as f32);
+                    if !BarycentricSetup::is_inside(weights) {
+                        continue;
+                    }
+                    let (f0, f1, f2) = weights;
+                    let vertex = v0 * f0 + v1 * f1 + v2 * f2;
+                    let color = self.pixel_shader.shade(uniforms, &vertex);
+                    self.target.set((x as u32, y as u32), &color);
+                }
+            }
+        }
+    }
+}
+
+/// Error produced while loading or decoding a mesh, distinguishing a missing
+/// or unreadable file from content (OBJ text, an interleaved vertex buffer)
+/// that doesn't parse.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse(String)
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "io error: {}", e),
+            LoadError::Parse(message) => write!(f, "parse error: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<std::io::Error> for LoadError {
+    fn from(e: std::io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+/// Parses Wavefront OBJ source text into a flat vertex/index buffer pair
+/// compatible with `draw_indexed_triangles`. Only `v`, `vt`, `vn` and `f`
+/// lines are understood; anything else is ignored. Faces with more than
+/// three vertices are fan-triangulated around their first vertex, and a
+/// face corner that omits a uv or normal index gets the zero vector for
+/// that attribute instead of being rejected.
+fn parse_obj(contents: &str) -> Result<(Vec<Vertex>, Vec<usize>), LoadError> {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut vertex_cache: std::collections::HashMap<(i32, i32, i32), usize> = std::collections::HashMap::new();
+
+    for line in contents.lines() {
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue
+        };
+
+        match keyword {
+            "v" => {
+                let parsed = parse_floats::<3>(tokens)
+                    .ok_or_else(|| LoadError::Parse(format!("malformed v line: {}", line)))?;
+                positions.push(glm::vec3(parsed[0], parsed[1], parsed[2]));
+            },
+            "vt" => {
+                let parsed = parse_floats::<2>(tokens)
+                    .ok_or_else(|| LoadError::Parse(format!("malformed vt line: {}", line)))?;
+                uvs.push(glm::vec2(parsed[0], parsed[1]));
+            },
+            "vn" => {
+                let parsed = parse_floats::<3>(tokens)
+                    .ok_or_else(|| LoadError::Parse(format!("malformed vn line: {}", line)))?;
+                normals.push(glm::vec3(parsed[0], parsed[1], parsed[2]));
+            },
+            "f" => {
+                let mut face_indices = Vec::new();
+                for corner in tokens {
+                    let key = parse_face_corner(corner)
+                        .ok_or_else(|| LoadError::Parse(format!("malformed f line: {}", line)))?;
+                    let vertex_index = *vertex_cache.entry(key).or_insert_with(|| {
+                        let (position_index, uv_index, normal_index) = key;
+                        let position = positions[(position_index - 1) as usize];
+                        let uv = if uv_index > 0 { uvs[(uv_index - 1) as usize] } else { glm::vec2(0.0, 0.0) };
+                        let normal = if normal_index > 0 { normals[(normal_index - 1) as usize] } else { glm::vec3(0.0, 0.0, 0.0) };
+                        vertices.push(Vertex { position, uv, normal });
+                        vertices.len() - 1
+                    });
+                    face_indices.push(vertex_index);
+                }
+
+                if face_indices.len() < 3 {
+                    return Err(LoadError::Parse(format!("face has fewer than 3 vertices: {}", line)));
+                }
+                for i in 1..face_indices.len() - 1 {
+                    indices.extend_from_slice(&[face_indices[0], face_indices[i], face_indices[i + 1]]);
+                }
+            },
+            _ => {}
+        }
+    }
+
+    Ok((vertices, indices))
+}
+
+/// Parses a `v/vt/vn` face corner, where `vt` and `vn` may be omitted
+/// (`v`, `v//vn` or `v/vt`). Missing indices are reported as 0, which is
+/// never a valid 1-based OBJ index and so doubles as an "absent" sentinel.
+fn parse_face_corner(corner: &str) -> Option<(i32, i32, i32)> {
+    let mut parts = corner.split('/');
+    let position_index = parts.next()?.parse::<i32>().ok()?;
+    let uv_index = match parts.next() {
+        Some("") | None => 0,
+        Some(value) => value.parse::<i32>().ok()?
+    };
+    let normal_index = match parts.next() {
+        Some("") | None => 0,
+        Some(value) => value.parse::<i32>().ok()?
+    };
+    Some((position_index, uv_index, normal_index))
+}
+
+fn parse_floats<const N: usize>(mut tokens: std::str::SplitWhitespace) -> Option<[f32; N]> {
+    let mut values = [0.0f32; N];
+    for value in values.iter_mut() {
+        *value = tokens.next()?.parse().ok()?;
+    }
+    Some(values)
+}
+
+pub fn load_obj(path: &str) -> Result<(Vec<Vertex>, Vec<usize>), LoadError> {
+    let contents = std::fs::read_to_string(path)?;
+    parse_obj(&contents)
+}
+
+// OBJ doesn't record winding, and exporters disagree on clockwise vs
+// counter-clockwise, so imported meshes sometimes render inside-out under
+// this crate's default `FrontFace`. Guesses the mesh's authored winding by
+// checking, for each triangle, whether the normal implied by its vertex
+// order (assuming counter-clockwise) points away from the mesh centroid or
+// towards it, and going with whichever direction a majority agree on.
+fn detect_front_face(vertices: &[Vertex], indices: &[usize]) -> FrontFace {
+    let centroid = vertices.iter().map(|v| v.position).sum::<glm::Vec3>() / vertices.len() as f32;
+
+    let mut outward = 0;
+    let mut inward = 0;
+    for triangle in indices.chunks_exact(3) {
+        let p0 = vertices[triangle[0]].position;
+        let p1 = vertices[triangle[1]].position;
+        let p2 = vertices[triangle[2]].position;
+        let normal = glm::cross(&(p1 - p0), &(p2 - p0));
+        let face_center = (p0 + p1 + p2) / 3.0;
+        if glm::dot(&normal, &(face_center - centroid)) >= 0.0 {
+            outward += 1;
+        } else {
+            inward += 1;
+        }
+    }
+
+    if outward >= inward { FrontFace::CounterClockwise } else { FrontFace::Clockwise }
+}
+
+// Like `load_obj`, but also guesses the mesh's authored winding via
+// `detect_front_face` so the caller can pass it straight to
+// `RenderContext::with_front_face` instead of guessing and flipping.
+pub fn load_obj_detecting_winding(path: &str) -> Result<(Vec<Vertex>, Vec<usize>, FrontFace), LoadError> {
+    let (vertices, indices) = load_obj(path)?;
+    let front_face = detect_front_face(&vertices, &indices);
+    Ok((vertices, indices, front_face))
+}
+
+// Describes where each `Vertex` attribute lives within one record of an
+// interleaved vertex buffer, e.g. one already produced by a glTF-style
+// interleaved accessor. `position` and `normal` are 3 contiguous
+// little-endian `f32`s, `uv` is 2; `stride` is the byte size of one record,
+// which may be larger than the attributes themselves if the source buffer
+// interleaves other data (skinning weights, tangents, ...) this crate
+// doesn't read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexLayout {
+    pub stride: usize,
+    pub position_offset: usize,
+    pub uv_offset: usize,
+    pub normal_offset: usize
+}
+
+impl VertexLayout {
+    // The layout of a `&[Vertex]` reinterpreted as bytes: fields packed in
+    // declaration order with no padding between records.
+    pub const INTERLEAVED_POSITION_UV_NORMAL: VertexLayout = VertexLayout {
+        stride: 8 * 4,
+        position_offset: 0,
+        uv_offset: 3 * 4,
+        normal_offset: 5 * 4
+    };
+}
+
+// Decouples the renderer's own `Vertex` from whatever interleaved format an
+// external asset pipeline hands you: reads one `Vertex` out of `buffer` per
+// `layout.stride` bytes, at the attribute offsets `layout` describes.
+pub fn vertices_from_interleaved_buffer(buffer: &[u8], layout: &VertexLayout) -> Result<Vec<Vertex>, LoadError> {
+    if layout.stride == 0 || !buffer.len().is_multiple_of(layout.stride) {
+        return Err(LoadError::Parse(format!(
+            "interleaved buffer of {} bytes is not a whole multiple of the {}-byte stride",
+            buffer.len(), layout.stride)));
+    }
+
+    let read_f32 = |record: &[u8], offset: usize| -> Result<f32, LoadError> {
+        let bytes = record.get(offset..offset + 4).ok_or_else(|| LoadError::Parse(format!(
+            "attribute offset {} is out of bounds for a {}-byte record", offset, record.len())))?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let read_vec3 = |record: &[u8], offset: usize| -> Result<glm::Vec3, LoadError> {
+        Ok(glm::vec3(read_f32(record, offset)?, read_f32(record, offset + 4)?, read_f32(record, offset + 8)?))
+    };
+    let read_vec2 = |record: &[u8], offset: usize| -> Result<glm::Vec2, LoadError> {
+        Ok(glm::vec2(read_f32(record, offset)?, read_f32(record, offset + 4)?))
+    };
+
+    buffer.chunks_exact(layout.stride).map(|record| {
+        Ok(Vertex {
+            position: read_vec3(record, layout.position_offset)?,
+            uv: read_vec2(record, layout.uv_offset)?,
+            normal: read_vec3(record, layout.normal_offset)?
+        })
+    }).collect()
+}
+
+pub fn write_checkerboard_png(path: &std::path::Path, size: u32, cell: u32) {
+    let image = image::ImageBuffer::from_fn(size, size, |x, y| {
+        if (x / cell + y / cell).is_multiple_of(2) {
+            image::Rgba([255u8, 255, 255, 255])
+        } else {
+            image::Rgba([32u8, 32, 32, 255])
+        }
+    });
+    image.save(path).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type NoopRenderContext<'a> = RenderContext<'a, 'static, Vertex,
+        fn(&mut Vertex) -> glm::Vec4,
+        fn(&Vertex) -> [u8; 4]>;
+
+    #[test]
+    fn texture_sample_clamps_out_of_range_uvs() {
+        let texture = Texture {
+            width: 2,
+            height: 2,
+            pixels: vec![
+                255, 0, 0, 255,    0, 255, 0, 255,
+                0, 0, 255, 255,    255, 255, 0, 255
+            ],
+            mip_levels: Vec::new(),
+            filter: FilterMode::Nearest,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        };
+
+        assert_eq!(texture.sample(glm::vec2(0.25, 0.25)), [255, 0, 0, 255]);
+        assert_eq!(texture.sample(glm::vec2(-1.0, -1.0)), [255, 0, 0, 255]);
+        assert_eq!(texture.sample(glm::vec2(2.0, 2.0)), [255, 255, 0, 255]);
+    }
+
+    #[test]
+    fn texture_sample_bilinear_averages_the_four_surrounding_texels() {
+        let texture = Texture {
+            width: 2,
+            height: 2,
+            pixels: vec![
+                255, 0, 0, 255,    0, 255, 0, 255,
+                0, 0, 255, 255,    255, 255, 0, 255
+            ],
+            mip_levels: Vec::new(),
+            filter: FilterMode::Bilinear,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        };
+
+        // Exactly between all four texels: the average of the four corners.
+        assert_eq!(texture.sample(glm::vec2(0.5, 0.5)), [128, 128, 64, 255]);
+    }
+
+    #[test]
+    fn sample_anisotropic_averages_texels_spread_along_the_stretched_derivative_axis() {
+        // An 8x8 grayscale gradient, brighter to the right, constant down
+        // each column, so only the horizontal derivative should matter.
+        let mut pixels = vec![0u8; 8 * 8 * 4];
+        for y in 0..8 {
+            for x in 0..8 {
+                let value = (x * 32) as u8;
+                let index = (y * 8 + x) * 4;
+                pixels[index..index + 4].copy_from_slice(&[value, value, value, 255]);
+            }
+        }
+        let mut texture = Texture::from_raw(8, 8, pixels).with_max_anisotropy(4);
+        texture.generate_mipmaps();
+
+        // Stretched a quarter of the texture per screen pixel horizontally
+        // (minifying strongly along U) but barely at all vertically, the
+        // grazing-angle ground-plane case anisotropic filtering targets.
+        let ddx = glm::vec2(0.25, 0.0);
+        let ddy = glm::vec2(0.0, 0.01);
+
+        // 4 samples spread along ddx at u = 0.40625, 0.46875, 0.53125,
+        // 0.59375 land on texel columns 3, 3, 4, 4 (values 96, 96, 128, 128),
+        // averaging to 112 - the spread an isotropic sample at the single
+        // (blurrier) mip level for the longer axis would never reproduce.
+        let sample = texture.sample_anisotropic(glm::vec2(0.5, 0.5), ddx, ddy);
+        assert_eq!(sample, [112, 112, 112, 255]);
+    }
+
+    #[test]
+    fn compute_tangents_yields_unit_orthogonal_tangents_for_a_flat_quad() {
+        let vertices = [
+            Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 0.0, 0.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let tangents = compute_tangents(&vertices, &indices);
+
+        assert_eq!(tangents.len(), vertices.len());
+        for (tangent, vertex) in tangents.iter().zip(&vertices) {
+            assert!((glm::length(tangent) - 1.0).abs() < 1e-4);
+            assert!(glm::dot(tangent, &vertex.normal).abs() < 1e-4);
+        }
+        // U increases along +X for this quad, so the tangent should too.
+        assert!(tangents[0].x > 0.9);
+    }
+
+    #[test]
+    fn compute_flat_normals_points_along_positive_z_for_a_counter_clockwise_xy_triangle() {
+        let vertices = [
+            Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(1.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let (out_vertices, out_indices) = compute_flat_normals(&vertices, &indices);
+
+        assert_eq!(out_vertices.len(), 3);
+        assert_eq!(out_indices, vec![0, 1, 2]);
+        for vertex in &out_vertices {
+            assert!((vertex.normal - glm::vec3(0.0, 0.0, 1.0)).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn compute_smooth_normals_averages_two_adjacent_faces_at_their_shared_edge() {
+        // Two triangles sharing the edge (1,0,0)-(0,1,0): one flat in the XY
+        // plane, the other folded down to (0,0,-1) along that same edge.
+        // The shared vertices should end up with the (normalized) sum of
+        // both faces' normals; the two apexes should keep their own face's
+        // exact normal untouched.
+        let mut vertices = vec![
+            Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(1.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(0.0, 0.0, -1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) }
+        ];
+        let indices = [0, 1, 2, 1, 2, 3];
+
+        let face_normal = |a: glm::Vec3, b: glm::Vec3, c: glm::Vec3| glm::cross(&(b - a), &(c - a));
+        let normal1 = face_normal(vertices[0].position, vertices[1].position, vertices[2].position);
+        let normal2 = face_normal(vertices[1].position, vertices[2].position, vertices[3].position);
+
+        compute_smooth_normals(&mut vertices, &indices);
+
+        assert!((vertices[0].normal - glm::normalize(&normal1)).norm() < 1e-5);
+        assert!((vertices[3].normal - glm::normalize(&normal2)).norm() < 1e-5);
+        let expected_shared = glm::normalize(&(normal1 + normal2));
+        assert!((vertices[1].normal - expected_shared).norm() < 1e-5);
+        assert!((vertices[2].normal - expected_shared).norm() < 1e-5);
+    }
+
+    #[test]
+    fn checkerboard_alternates_between_the_two_given_colors() {
+        let a = [255u8, 0, 0, 255];
+        let b = [0u8, 255, 0, 255];
+        let texture = Texture::checkerboard(2, 2, 1, a, b);
+
+        assert_eq!(texture.sample(glm::vec2(0.25, 0.25)), a);
+        assert_eq!(texture.sample(glm::vec2(0.75, 0.25)), b);
+        assert_eq!(texture.sample(glm::vec2(0.25, 0.75)), b);
+        assert_eq!(texture.sample(glm::vec2(0.75, 0.75)), a);
+    }
+
+    #[test]
+    fn from_encoded_bytes_decodes_an_embedded_png() {
+        // A hand-built 2x2 RGBA PNG: red, green / blue, white (row-major,
+        // top-left origin), embedded directly into the test binary.
+        let bytes = include_bytes!("testdata/tiny.png");
+        let texture = Texture::from_encoded_bytes(bytes).unwrap();
+
+        assert_eq!(texture.width, 2);
+        assert_eq!(texture.height, 2);
+        assert_eq!(texture.sample(glm::vec2(0.25, 0.25)), [255, 0, 0, 255]);
+        assert_eq!(texture.sample(glm::vec2(0.75, 0.75)), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn blend_colors_mixes_two_textures_by_a_uv_driven_mask() {
+        let brick = Texture::solid(1, 1, [200u8, 80, 40, 255]);
+        let moss = Texture::solid(1, 1, [40u8, 120, 40, 255]);
+        let sampler = Sampler::new(vec![&brick, &moss]);
+
+        let uv = glm::vec2(0.5, 0.5);
+        let mask = uv.y;
+        let blended = blend_colors(sampler.sample(0, uv), sampler.sample(1, uv), mask);
+
+        assert_eq!(blended, [120, 100, 40, 255]);
+        assert_eq!(blend_colors(sampler.sample(0, uv), sampler.sample(1, uv), 0.0), [200, 80, 40, 255]);
+        assert_eq!(blend_colors(sampler.sample(0, uv), sampler.sample(1, uv), 1.0), [40, 120, 40, 255]);
+    }
+
+    #[test]
+    fn generate_mipmaps_halves_dimensions_down_to_a_single_texel() {
+        let mut texture = Texture {
+            width: 4,
+            height: 4,
+            pixels: vec![128u8; 4 * 4 * 4],
+            mip_levels: Vec::new(),
+            filter: FilterMode::Nearest,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        };
+
+        texture.generate_mipmaps();
+
+        let sizes: Vec<(u32, u32)> = texture.mip_levels.iter().map(|(w, h, _)| (*w, *h)).collect();
+        assert_eq!(sizes, vec![(4, 4), (2, 2), (1, 1)]);
+        for (width, height, pixels) in &texture.mip_levels {
+            assert_eq!(pixels.len() as u32, width * height * 4);
+        }
+    }
+
+    #[test]
+    fn generate_mipmaps_floors_odd_dimensions_instead_of_reading_out_of_bounds() {
+        let mut texture = Texture {
+            width: 3,
+            height: 5,
+            pixels: vec![128u8; 3 * 5 * 4],
+            mip_levels: Vec::new(),
+            filter: FilterMode::Nearest,
+            wrap_u: WrapMode::Clamp,
+            wrap_v: WrapMode::Clamp,
+            max_anisotropy: 1
+        };
+
+        texture.generate_mipmaps();
+
+        // 3 floors to 1, 5 floors to 2; from there 1 stays 1 (clamped) and 2
+        // halves to 1, ending at a 1x1 level.
+        let sizes: Vec<(u32, u32)> = texture.mip_levels.iter().map(|(w, h, _)| (*w, *h)).collect();
+        assert_eq!(sizes, vec![(3, 5), (1, 2), (1, 1)]);
+        for (width, height, pixels) in &texture.mip_levels {
+            assert_eq!(pixels.len() as u32, width * height * 4);
+        }
+    }
+
+    #[test]
+    fn sample_nearest_addresses_texels_correctly_on_a_non_square_non_power_of_two_texture() {
+        // A 3x5 texture where each texel encodes its own (x, y) coordinate,
+        // so a wrong stride or transposed width/height shows up immediately.
+        let (width, height) = (3u32, 5u32);
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let index = ((y * width + x) * 4) as usize;
+                pixels[index..index + 4].copy_from_slice(&[x as u8, y as u8, 0, 255]);
+            }
+        }
+        let texture = Texture::from_raw(width, height, pixels);
+
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                assert_eq!(texture.sample(glm::vec2(u, v)), [x as u8, y as u8, 0, 255]);
+            }
+        }
+
+        // The far edge (uv == 1.0) must clamp to the last row/column rather
+        // than reading past the end of the pixel buffer.
+        assert_eq!(texture.sample(glm::vec2(1.0, 1.0)), [(width - 1) as u8, (height - 1) as u8, 0, 255]);
+    }
+
+    #[test]
+    fn save_png_round_trips_a_known_pattern_through_disk() {
+        let mut target = TextureBuffer::new((2, 2), 4);
+        target.set((0, 0), &[255, 0, 0, 255]);
+        target.set((1, 0), &[0, 255, 0, 255]);
+        target.set((0, 1), &[0, 0, 255, 255]);
+        target.set((1, 1), &[255, 255, 0, 128]);
+
+        let path = std::env::temp_dir().join("save_png_round_trip_test.png");
+        target.save_png(path.to_str().unwrap()).unwrap();
+
+        let reloaded = image::open(&path).unwrap().to_rgba8();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(reloaded.dimensions(), (2, 2));
+        assert_eq!(reloaded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(reloaded.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(reloaded.get_pixel(0, 1).0, [0, 0, 255, 255]);
+        assert_eq!(reloaded.get_pixel(1, 1).0, [255, 255, 0, 128]);
+    }
+
+    #[test]
+    fn save_png_errors_clearly_when_bytes_per_pixel_is_not_four() {
+        let target = TextureBuffer::new((2, 2), 3);
+        assert!(target.save_png("/tmp/should_not_be_written.png").is_err());
+    }
+
+    // Renders a small textured cube from a fixed camera angle into a
+    // headless buffer, with no randomness anywhere in the pipeline, so the
+    // exact same bytes come out on every run and can be diffed against a
+    // committed golden PNG.
+    fn render_textured_cube_scene() -> TextureBuffer<'static> {
+        let cube_vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, 1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0,  1.0, 1.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, 1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+
+            Vertex { position: glm::vec3(1.0,  1.0,  1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(1.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(1.0,  1.0, -1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(1.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, -1.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(1.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(1.0, -1.0,  1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(1.0, 0.0, 0.0) },
+
+            Vertex { position: glm::vec3(-1.0, -1.0, -1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, -1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+            Vertex { position: glm::vec3( 1.0,  1.0, -1.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, -1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+
+            Vertex { position: glm::vec3(-1.0, -1.0, -1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(-1.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0, -1.0,  1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(-1.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0,  1.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(-1.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, -1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(-1.0, 0.0, 0.0) },
+
+            Vertex { position: glm::vec3( 1.0, 1.0,  1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 1.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0,  1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 1.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, -1.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 1.0, 0.0) },
+            Vertex { position: glm::vec3( 1.0, 1.0, -1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 1.0, 0.0) },
+
+            Vertex { position: glm::vec3(-1.0, -1.0, -1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, -1.0, 0.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, -1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, -1.0, 0.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0,  1.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, -1.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0, -1.0,  1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, -1.0, 0.0) }
+        ];
+        let cube_indices = [
+            0,  2,  1,  0,  3,  2,
+            4,  5,  6,  4,  6,  7,
+            8,  9,  10, 8,  10, 11,
+            12, 14, 13, 12, 15, 14,
+            16, 17, 18, 16, 18, 19,
+            20, 22, 21, 20, 23, 22
+        ];
+
+        let texture = Texture::checkerboard(8, 8, 2, [200u8, 60, 60, 255], [60u8, 60, 200, 255]);
+
+        let mut camera = Camera::new(1.0, std::f32::consts::PI / 4.0, 0.1, 100.0);
+        camera.look_at(glm::vec3(2.5, 2.0, 4.0), glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0));
+        let model = glm::rotation(0.6, &glm::vec3(0.0, 1.0, 0.0)) * glm::rotation(0.3, &glm::vec3(1.0, 0.0, 0.0));
+        let mvp = camera.mvp(&model);
+
+        let mut target = TextureBuffer::new((32, 32), 4);
+        let mut depth = DepthBuffer::new((32, 32));
+        RenderContext::new(
+            CullMode::Back,
+            &mut target,
+            |v: &mut Vertex| mvp * glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| texture.sample(v.uv)
+        ).with_depth_test(&mut depth, DepthFunc::Less)
+         .draw_indexed_triangles(&cube_indices, &cube_vertices);
+
+        target
+    }
+
+    #[test]
+    fn textured_cube_rendering_matches_the_committed_golden_image() {
+        let rendered = render_textured_cube_scene();
+
+        let golden_bytes = include_bytes!("testdata/textured_cube_golden.png");
+        let golden_image = image::load_from_memory(golden_bytes).unwrap().to_rgba8();
+        let mut golden_pixels = golden_image.into_raw();
+        let golden = TextureBuffer::from_slice(&mut golden_pixels, (32, 32), 4);
+
+        if let Some(diff_stats) = compare_images(&rendered, &golden, 2) {
+            let diff_path = std::env::temp_dir().join("textured_cube_golden_diff.png");
+            let mut diff = TextureBuffer::new((32, 32), 4);
+            for y in 0..32 {
+                for x in 0..32 {
+                    let a = rendered.get((x, y));
+                    let b = golden.get((x, y));
+                    let mut pixel = [0u8; 4];
+                    for channel in 0..4 {
+                        pixel[channel] = a[channel].abs_diff(b[channel]);
+                    }
+                    pixel[3] = 255;
+                    diff.set((x, y), &pixel);
+                }
+            }
+            diff.save_png(diff_path.to_str().unwrap()).unwrap();
+
+            panic!(
+                "rendered cube does not match golden image ({} differing pixels, max channel diff {}); diff written to {}",
+                diff_stats.differing_pixels, diff_stats.max_channel_diff, diff_path.display()
+            );
+        }
+    }
+
+    #[test]
+    fn gif_recorder_encodes_two_captured_frames_and_decodes_back_to_the_right_size_and_count() {
+        let mut first = TextureBuffer::new((2, 2), 4);
+        first.set((0, 0), &[255, 0, 0, 255]);
+        let mut second = TextureBuffer::new((2, 2), 4);
+        second.set((0, 0), &[0, 255, 0, 255]);
+
+        let path = std::env::temp_dir().join("gif_recorder_test.gif");
+        {
+            // A very low `max_fps` would only be a problem for capturing
+            // frames back to back in real time; there's no rate limit to
+            // worry about here since each capture is forced through
+            // regardless by asserting on `last_capture` being `None` for
+            // the very first one and giving the second an already-elapsed
+            // window via a generous 1000 fps cap.
+            let mut recorder = GifRecorder::new(path.to_str().unwrap(), (2, 2), 1000.0).unwrap();
+            recorder.capture(&first).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            recorder.capture(&second).unwrap();
+        }
+
+        let file = std::fs::File::open(&path).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(file).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (2, 2));
+
+        let mut frame_count = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frame_count += 1;
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(frame_count, 2);
+    }
+
+    #[test]
+    fn resize_reallocates_the_buffer_to_the_new_size_and_clears_it() {
+        let mut target = TextureBuffer::new((2, 2), 4);
+        target.set_checked((0, 0), &[255, 255, 255, 255]);
+
+        target.resize((3, 2));
+
+        assert_eq!(target.buffer.len(), 3 * 2 * 4);
+        assert_eq!(target.get((0, 0)), [0, 0, 0, 0]);
+        assert!(target.set_checked((2, 1), &[10, 20, 30, 40]));
+        assert_eq!(target.get((2, 1)), [10, 20, 30, 40]);
+    }
+
+    #[test]
+    fn from_slice_renders_into_a_stack_allocated_array_instead_of_allocating() {
+        let mut backing = [0u8; 2 * 2 * 4];
+        let mut target = TextureBuffer::from_slice(&mut backing, (2, 2), 4);
+
+        target.set_checked((1, 1), &[255, 0, 0, 255]);
+
+        assert_eq!(target.get((1, 1)), [255, 0, 0, 255]);
+        assert_eq!(target.get((0, 0)), [0, 0, 0, 0]);
+        assert_eq!(backing[12..16], [255, 0, 0, 255]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_slice_panics_when_the_slice_length_does_not_match_size_and_bytes_per_pixel() {
+        let mut backing = [0u8; 4];
+        TextureBuffer::from_slice(&mut backing, (2, 2), 4);
+    }
+
+    #[test]
+    fn set_checked_rejects_out_of_bounds_points_without_writing() {
+        let mut target = TextureBuffer::new((2, 2), 4);
+
+        assert!(target.set_checked((1, 1), &[10, 20, 30, 40]));
+        assert_eq!(target.get((1, 1)), [10, 20, 30, 40]);
+
+        assert!(!target.set_checked((2, 0), &[255, 255, 255, 255]));
+        assert!(!target.set_checked((0, 2), &[255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn clear_gradient_interpolates_to_the_midpoint_color_at_the_vertical_midpoint() {
+        let mut target = TextureBuffer::new((2, 11), 4);
+
+        target.clear_gradient([200, 0, 0, 255], [0, 100, 0, 255]);
+
+        assert_eq!(target.get((0, 0)), [200, 0, 0, 255]);
+        assert_eq!(target.get((0, 10)), [0, 100, 0, 255]);
+        assert_eq!(target.get((0, 5)), [100, 50, 0, 255]);
+    }
+
+    #[test]
+    fn dirty_rect_tightly_bounds_the_points_written_since_the_last_clear() {
+        let mut target = TextureBuffer::new((8, 8), 4);
+        assert_eq!(target.dirty_rect(), None);
+
+        target.set((3, 2), &[255, 0, 0, 255]);
+        target.set((5, 4), &[0, 255, 0, 255]);
+        target.set((4, 1), &[0, 0, 255, 255]);
+
+        assert_eq!(target.dirty_rect(), Some((3, 1, 3, 4)));
+
+        target.reset_dirty();
+        assert_eq!(target.dirty_rect(), None);
+
+        target.set((0, 0), &[1, 2, 3, 4]);
+        assert_eq!(target.dirty_rect(), Some((0, 0, 1, 1)));
+
+        target.clear(0);
+        assert_eq!(target.dirty_rect(), None);
+    }
+
+    #[test]
+    fn stats_report_submitted_triangles_and_shaded_fragments_matching_pixel_coverage() {
+        // Two triangles forming a rectangle over the left half of NDC space
+        // (x in [-1, 0]), which on a 4-pixel-wide target covers exactly
+        // columns 0-1 of all 4 rows: 8 fragments, no culling or clipping.
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        );
+        render_context.draw_indexed_triangles(&indices, &vertices);
+
+        let stats = render_context.stats();
+        assert_eq!(stats.triangles_submitted, 2);
+        assert_eq!(stats.triangles_culled, 0);
+        assert_eq!(stats.triangles_clipped, 0);
+        assert_eq!(stats.fragments_shaded, 8);
+        assert_eq!(stats.fragments_depth_rejected, 0);
+
+        render_context.reset_stats();
+        assert_eq!(render_context.stats(), RenderStats::default());
+    }
+
+    #[test]
+    fn a_triangle_entirely_outside_the_scissor_rect_is_rejected_before_tessellation() {
+        // The triangle sits entirely in the bottom-left corner of the target
+        // and survives NDC clipping, but the scissor rect only covers the
+        // opposite (top-right) corner, so its screen-space bbox misses the
+        // scissor area entirely.
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.6, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.9, -0.6, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        );
+        render_context.set_scissor(Some((3, 3, 1, 1)));
+        render_context.draw_indexed_triangles(&indices, &vertices);
+
+        let stats = render_context.stats();
+        assert_eq!(stats.triangles_submitted, 1);
+        assert_eq!(stats.triangles_offscreen, 1);
+        assert_eq!(stats.fragments_shaded, 0);
+    }
+
+    #[test]
+    fn renders_a_triangle_headlessly_without_opening_a_window() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn renderer_install_draws_the_same_result_as_the_global_pool() {
+        let renderer = Renderer::new(2);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let mut via_pool = TextureBuffer::new((4, 4), 4);
+        renderer.install(|| {
+            RenderContext::new(
+                CullMode::None,
+                &mut via_pool,
+                |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                |_: &Vertex| [0u8, 200, 80, 255]
+            ).draw_indexed_triangles(&indices, &vertices);
+        });
+
+        let mut via_global = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut via_global,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(*via_pool.buffer, *via_global.buffer);
+    }
+
+    #[test]
+    fn draw_quad_covers_the_same_pixels_as_its_two_equivalent_triangles() {
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.9, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+
+        let mut quad_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut quad_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_quad(vertices[0], vertices[1], vertices[2], vertices[3]);
+
+        let mut triangles_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut triangles_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&[0, 1, 2, 0, 2, 3], &vertices);
+
+        assert_eq!(*quad_target.buffer, *triangles_target.buffer);
+        assert_eq!(quad_target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn fill_triangle_covers_the_same_pixels_as_its_indexed_equivalent() {
+        let vertex = |x: f32, y: f32| Vertex { position: glm::vec3(x, y, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let vertices = [vertex(-0.9, -0.9), vertex(0.9, -0.9), vertex(0.0, 0.9)];
+        let clip_position = |v: &Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0);
+
+        let mut fill_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut fill_target,
+            |v: &mut Vertex| clip_position(v),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).fill_triangle(
+            (clip_position(&vertices[0]), vertices[0]),
+            (clip_position(&vertices[1]), vertices[1]),
+            (clip_position(&vertices[2]), vertices[2])
+        );
+
+        let mut indexed_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut indexed_target,
+            |v: &mut Vertex| clip_position(v),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&[0, 1, 2], &vertices);
+
+        assert_eq!(*fill_target.buffer, *indexed_target.buffer);
+        assert_eq!(fill_target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn draw_with_covers_the_same_pixels_as_the_indexed_equivalent() {
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.9, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+
+        let mut immediate_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut immediate_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_with(|sink| {
+            sink.triangle(vertices[0], vertices[1], vertices[2]);
+            sink.triangle(vertices[0], vertices[2], vertices[3]);
+        });
+
+        let mut indexed_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut indexed_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&[0, 1, 2, 0, 2, 3], &vertices);
+
+        assert_eq!(*immediate_target.buffer, *indexed_target.buffer);
+        assert_eq!(immediate_target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn draw_polygon_fans_a_convex_pentagon_from_its_first_vertex() {
+        let make_vertex = |x: f32, y: f32| Vertex { position: glm::vec3(x, y, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let verts = [
+            make_vertex(0.0, -0.9),
+            make_vertex(0.9, -0.2),
+            make_vertex(0.55, 0.9),
+            make_vertex(-0.55, 0.9),
+            make_vertex(-0.9, -0.2)
+        ];
+
+        let mut polygon_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut polygon_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_polygon(&verts);
+
+        let mut fan_target = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut fan_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&[0, 1, 2, 0, 2, 3, 0, 3, 4], &verts);
+
+        assert_eq!(*polygon_target.buffer, *fan_target.buffer);
+        assert_eq!(polygon_target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn cull_mode_respects_triangle_winding_and_front_face() {
+        // Ordered 0, 1, 2 this triangle winds counter-clockwise in NDC;
+        // reversing the last two indices winds it clockwise instead.
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let ccw_indices = [0, 1, 2];
+        let cw_indices = [0, 2, 1];
+
+        let draws = |cull_mode: CullMode, indices: &[usize]| {
+            let mut target = TextureBuffer::new((4, 4), 4);
+            RenderContext::new(
+                cull_mode,
+                &mut target,
+                |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                |_: &Vertex| [0u8, 200, 80, 255]
+            ).draw_indexed_triangles(indices, &vertices);
+            target.get((2, 2)) != [0, 0, 0, 0]
+        };
+
+        assert!(draws(CullMode::None, &ccw_indices));
+        assert!(draws(CullMode::None, &cw_indices));
+        assert!(draws(CullMode::Back, &ccw_indices));
+        assert!(!draws(CullMode::Back, &cw_indices));
+        assert!(!draws(CullMode::Front, &ccw_indices));
+        assert!(draws(CullMode::Front, &cw_indices));
+    }
+
+    #[test]
+    fn front_face_clockwise_flips_which_winding_counts_as_front() {
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let cw_indices = [0, 2, 1];
+        let mut target = TextureBuffer::new((4, 4), 4);
+
+        RenderContext::new(
+            CullMode::Back,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).with_front_face(FrontFace::Clockwise)
+         .draw_indexed_triangles(&cw_indices, &vertices);
+
+        assert_eq!(target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn triangle_strip_expands_five_indices_into_three_triangles_with_alternating_winding() {
+        let indices = [0usize, 1, 2, 3, 4];
+
+        let triangles = NoopRenderContext::triangle_indices(PrimitiveTopology::TriangleStrip, &indices, None);
+
+        // Every other triangle has its first two indices swapped so each one
+        // keeps the same front face as the one before it.
+        assert_eq!(triangles, vec![(0, 1, 2), (2, 1, 3), (2, 3, 4)]);
+    }
+
+    #[test]
+    fn triangle_fan_expands_five_indices_into_three_triangles_sharing_the_first_vertex() {
+        let indices = [0usize, 1, 2, 3, 4];
+
+        let triangles = NoopRenderContext::triangle_indices(PrimitiveTopology::TriangleFan, &indices, None);
+
+        assert_eq!(triangles, vec![(0, 1, 2), (0, 2, 3), (0, 3, 4)]);
+    }
+
+    #[test]
+    fn restart_index_splits_a_strip_into_independent_runs_with_no_connecting_triangle() {
+        let restart = usize::MAX;
+        let indices = [0usize, 1, 2, 3, restart, 4, 5, 6, 7];
+
+        let triangles = NoopRenderContext::triangle_indices(PrimitiveTopology::TriangleStrip, &indices, Some(restart));
+
+        // Each strip is expanded exactly as if it had been submitted alone
+        // (including its own winding alternation restarting from `i == 0`);
+        // nothing connects index 3 (the end of the first strip) to index 4
+        // (the start of the second).
+        assert_eq!(triangles, vec![(0, 1, 2), (2, 1, 3), (4, 5, 6), (6, 5, 7)]);
+    }
+
+    #[test]
+    fn draws_a_clipped_diagonal_line_with_bresenham() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.5, 0.5, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_lines(&indices, &vertices, LineTopology::LineList);
+
+        for i in 0..4 {
+            assert_eq!(target.get((i, i)), [0, 200, 80, 255]);
+        }
+        assert_eq!(target.get((0, 3)), [0, 0, 0, 0]);
+        assert_eq!(target.get((3, 0)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn draw_points_plots_a_square_of_the_given_size_at_each_vertex() {
+        // NDC x/y of -2/3, 0, 2/3 land exactly on screen centers 2, 6, 10 in a
+        // 12-wide target, spaced far enough apart for their 3x3 blocks not to touch.
+        let mut target = TextureBuffer::new((12, 12), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-2.0 / 3.0, -2.0 / 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(2.0 / 3.0, 2.0 / 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_points(&indices, &vertices, 3);
+
+        for &center in &[2u32, 6, 10] {
+            for dy in 0..3 {
+                for dx in 0..3 {
+                    assert_eq!(target.get((center - 1 + dx, center - 1 + dy)), [0, 200, 80, 255]);
+                }
+            }
+        }
+        // Outside any block.
+        assert_eq!(target.get((4, 4)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn sort_transparent_composites_back_to_front_regardless_of_index_order() {
+        // Red lives at z=0.5 (far) and blue at z=-0.5 (near); the pixel shader
+        // reads the interpolated position to tell them apart. Without sorting,
+        // which one lands on top would depend on index order.
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.9, -0.9, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let pixel_shader = |v: &Vertex| if v.position.z < 0.0 { [0u8, 0, 255, 128] } else { [255u8, 0, 0, 128] };
+
+        let mut near_first = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut near_first,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            pixel_shader
+        ).with_blend_mode(BlendMode::AlphaBlend)
+         .with_sort_transparent(true)
+         .draw_indexed_triangles(&[3, 4, 5, 0, 1, 2], &vertices);
+
+        let mut far_first = TextureBuffer::new((4, 4), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut far_first,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            pixel_shader
+        ).with_blend_mode(BlendMode::AlphaBlend)
+         .with_sort_transparent(true)
+         .draw_indexed_triangles(&[0, 1, 2, 3, 4, 5], &vertices);
+
+        assert_eq!(near_first.get((2, 2)), far_first.get((2, 2)));
+        assert_eq!(near_first.get((2, 2)), [64, 0, 128, 96]);
+    }
+
+    #[test]
+    fn output_srgb_encodes_a_linear_half_gray_to_roughly_188() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [128u8, 128, 128, 255]
+        ).with_output_srgb(true)
+         .draw_indexed_triangles(&indices, &vertices);
+
+        // The alpha channel is untouched; only RGB goes through the curve.
+        assert_eq!(target.get((2, 2)), [188, 188, 188, 255]);
+    }
+
+    #[test]
+    fn discarding_a_vertex_skips_every_triangle_that_references_it() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new_with_cull(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| if v.position.y > 0.0 {
+                None
+            } else {
+                Some(glm::vec4(v.position.x, v.position.y, v.position.z, 1.0))
+            },
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((2, 2)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn discarding_a_pixel_leaves_it_at_the_clear_value() {
+        // A full-target quad whose pixel shader cuts out the left half, like
+        // an alpha-tested texture would.
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new_with_pixel_discard(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| if v.position.x < 0.0 {
+                None
+            } else {
+                Some([0u8, 200, 80, 255])
+            }
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((0, 2)), [0, 0, 0, 0]);
+        assert_eq!(target.get((3, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn color_write_false_updates_depth_but_leaves_color_untouched() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let mut depth = DepthBuffer::new((4, 4));
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.25), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.25), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.25), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.25), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).with_depth_test(&mut depth, DepthFunc::Less)
+         .with_color_write(false)
+         .draw_indexed_triangles(&indices, &vertices);
+
+        // No color was written...
+        assert_eq!(target.get((2, 2)), [0, 0, 0, 0]);
+
+        // ...but the depth buffer did update, so a second pass gated on
+        // `DepthFunc::Equal` still shades the pixel.
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).with_depth_test(&mut depth, DepthFunc::Equal)
+         .draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn hierarchical_z_rejects_a_fully_occluded_triangle_with_zero_fragments() {
+        let mut target = TextureBuffer::new((8, 8), 4);
+        let mut depth = DepthBuffer::new((8, 8));
+        // Something already covers the whole target nearer than -0.5.
+        depth.clear_depth(-0.5);
+        let hi_z = HierarchicalDepthBuffer::build(&depth);
+
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [200u8, 100, 50, 255]
+        ).with_depth_test(&mut depth, DepthFunc::Less)
+         .with_hierarchical_z(&hi_z);
+        render_context.draw_indexed_triangles(&indices, &vertices);
+
+        let stats = render_context.stats();
+        assert_eq!(stats.fragments_shaded, 0);
+        assert_eq!(stats.triangles_occlusion_culled, 2);
+        assert_eq!(target.get((4, 4)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn hierarchical_z_avoids_rasterizing_occluded_geometry_that_a_plain_depth_test_still_visits() {
+        // Stands in for the benchmark this feature would traditionally get
+        // (this crate has no bench harness): a per-pixel depth test already
+        // reduces `fragments_shaded` to zero for fully hidden geometry either
+        // way, so the metric that actually shows hierarchical-Z's savings is
+        // how many pixels got rasterized *at all* — `OverdrawBuffer` counts
+        // exactly that, "regardless of whether the depth test then rejected
+        // it".
+        let quad = |z: f32| [
+            Vertex { position: glm::vec3(-1.0, -1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let overdraw_after_hidden_geometry = |use_hi_z: bool| {
+            let mut target = TextureBuffer::new((16, 16), 4);
+            let mut depth = DepthBuffer::new((16, 16));
+
+            // Nearest quad first, as a front-to-back renderer would draw it.
+            RenderContext::new(
+                CullMode::None, &mut target,
+                |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                |_: &Vertex| [200u8, 100, 50, 255]
+            ).with_depth_test(&mut depth, DepthFunc::Less)
+             .draw_indexed_triangles(&indices, &quad(-0.9));
+
+            let hi_z = HierarchicalDepthBuffer::build(&depth);
+            let mut overdraw = OverdrawBuffer::new((16, 16));
+            for z in [-0.5, -0.2, 0.0, 0.3, 0.6] {
+                let mut render_context = RenderContext::new(
+                    CullMode::None, &mut target,
+                    |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                    |_: &Vertex| [10u8, 20, 30, 255]
+                ).with_depth_test(&mut depth, DepthFunc::Less)
+                 .with_overdraw_tracking(&mut overdraw);
+                if use_hi_z {
+                    render_context = render_context.with_hierarchical_z(&hi_z);
+                }
+                render_context.draw_indexed_triangles(&indices, &quad(z));
+            }
+
+            (0..16).flat_map(|x| (0..16).map(move |y| (x, y)))
+                .map(|point| overdraw.get(point) as u64)
+                .sum::<u64>()
+        };
+
+        let without_hi_z = overdraw_after_hidden_geometry(false);
+        let with_hi_z = overdraw_after_hidden_geometry(true);
+
+        assert_eq!(with_hi_z, 0);
+        assert!(with_hi_z < without_hi_z);
+    }
+
+    #[test]
+    fn stencil_mask_limits_a_later_full_screen_triangle_to_the_masked_area() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let mut stencil = StencilBuffer::new((4, 4));
+
+        // Paint a mask covering the left half of the target.
+        let mask_vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let mask_indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 0, 0, 0]
+        ).with_stencil_test(&mut stencil, StencilFunc::Always, 1, StencilOp::Replace)
+         .with_color_write(false)
+         .draw_indexed_triangles(&mask_indices, &mask_vertices);
+
+        // Then draw a full-screen triangle, only colored where the mask was set.
+        let full_screen_vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let full_screen_indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).with_stencil_test(&mut stencil, StencilFunc::Equal, 1, StencilOp::Keep)
+         .draw_indexed_triangles(&full_screen_indices, &full_screen_vertices);
+
+        assert_eq!(target.get((0, 2)), [0, 200, 80, 255]);
+        assert_eq!(target.get((3, 2)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn pick_returns_the_id_of_the_triangle_drawn_at_a_point_and_none_where_nothing_was_drawn() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let mut ids = IdBuffer::new((4, 4));
+
+        // Left half, id 1.
+        let left_vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let left_indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 0, 0, 255]
+        ).with_id_buffer(&mut ids, 1)
+         .draw_indexed_triangles(&left_indices, &left_vertices);
+
+        // Right half, id 2.
+        let right_vertices = [
+            Vertex { position: glm::vec3(0.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let right_indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 255, 0, 255]
+        ).with_id_buffer(&mut ids, 2)
+         .draw_indexed_triangles(&right_indices, &right_vertices);
+
+        assert_eq!(ids.pick((0, 2)), Some(1));
+        assert_eq!(ids.pick((3, 2)), Some(2));
+    }
+
+    #[test]
+    fn linear_fog_is_unmodified_at_start_and_fully_fog_colored_at_end() {
+        let fog = Fog { color: [255, 255, 255, 255], mode: FogMode::Linear, start: 0.2, end: 0.8, density: 0.0 };
+
+        let triangle_at_depth = |depth: f32| {
+            let mut target = TextureBuffer::new((4, 4), 4);
+            let vertices = [
+                Vertex { position: glm::vec3(-1.0, -1.0, depth), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+                Vertex { position: glm::vec3(3.0, -1.0, depth), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+                Vertex { position: glm::vec3(-1.0, 3.0, depth), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+            ];
+            let indices = [0, 1, 2];
+
+            RenderContext::new(
+                CullMode::None,
+                &mut target,
+                |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                |_: &Vertex| [0u8, 200, 80, 255]
+            ).with_fog(fog).draw_indexed_triangles(&indices, &vertices);
+
+            target.get((2, 2))
+        };
+
+        assert_eq!(triangle_at_depth(0.2), [0, 200, 80, 255]);
+        assert_eq!(triangle_at_depth(0.8), [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn barycentric_inside_coverage_test_renders_the_same_interior_pixel_as_the_top_left_rule() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 200, 80, 255]
+        ).with_coverage_test(CoverageTest::BarycentricInside)
+         .draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((2, 2)), [0, 200, 80, 255]);
+    }
+
+    #[test]
+    fn coverage_anti_alias_blends_a_half_covered_silhouette_pixel() {
+        let mut target = TextureBuffer::new((4, 8), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(0.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [200u8, 100, 50, 255]
+        ).with_anti_alias(AntiAlias::Coverage)
+         .draw_indexed_triangles(&indices, &vertices);
+
+        // The triangle's left edge lands exactly on column 2, so that column's
+        // pixels are ~50% covered and blend halfway toward the cleared (black)
+        // background instead of being fully in or out.
+        assert_eq!(target.get((2, 4)), [100, 50, 25, 128]);
+    }
+
+    #[test]
+    fn conservative_rasterization_covers_a_sliver_the_default_coverage_test_drops_entirely() {
+        // A sliver less than a pixel tall, sitting strictly between
+        // integer rows 2 and 3, so under the default `SampleConvention`
+        // (a corner sample at each pixel's top-left, offset 0.0) no sample
+        // point ever falls inside it.
+        let vertices = [
+            Vertex { position: glm::vec3(0.5, 2.2, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(4.5, 2.2, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(2.5, 2.8, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        // Vertex positions above are already screen-space pixel
+        // coordinates for a 5x5 target; map them back to NDC so the
+        // default viewport transform lands them back exactly there.
+        let to_ndc = |v: &mut Vertex| glm::vec4(v.position.x / 2.5 - 1.0, v.position.y / 2.5 - 1.0, v.position.z, 1.0);
+
+        let render = |conservative: bool| {
+            let mut target = TextureBuffer::new((5, 5), 4);
+            RenderContext::new(
+                CullMode::None,
+                &mut target,
+                to_ndc,
+                |_: &Vertex| [200u8, 100, 50, 255]
+            ).with_conservative_rasterization(conservative)
+             .draw_indexed_triangles(&indices, &vertices);
+            (0..5).flat_map(|x| (0..5).map(move |y| (x, y))).any(|(x, y)| target.get((x, y)) != [0, 0, 0, 0])
+        };
+
+        assert!(!render(false));
+        assert!(render(true));
+    }
+
+    #[test]
+    fn view_space_normal_cull_disagrees_with_screen_space_under_an_axis_flipping_orthographic_projection() {
+        // An orthographic projector that flips Y when going from view space to
+        // clip space (a common convention mismatch, e.g. Y-up view space vs.
+        // Y-down screen space) also flips the sign of the screen-space 2D
+        // cross product, so `CullMethod::ScreenSpace` gets every triangle
+        // backwards. `CullMethod::ViewSpaceNormal` looks at the true
+        // pre-projection positions and isn't fooled.
+        let project = |v: &mut Vertex| glm::vec4(v.position.x, -v.position.y, v.position.z, 1.0);
+        let view_position = |v: &Vertex| v.position;
+
+        // Wound CCW in view space: genuinely facing the camera.
+        let facing_camera = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        // Same vertices, wound CW in view space: genuinely facing away.
+        let facing_away = [
+            facing_camera[0], facing_camera[2], facing_camera[1]
+        ];
+        let indices = [0, 1, 2];
+
+        let render = |vertices: &[Vertex; 3], cull_method: CullMethod| {
+            let mut target = TextureBuffer::new((4, 4), 4);
+            let mut context = RenderContext::new(
+                CullMode::Back,
+                &mut target,
+                project,
+                |_: &Vertex| [200u8, 100, 50, 255]
+            ).with_cull_method(cull_method);
+            context.draw_indexed_triangles_with_view_space_cull(&indices, vertices, view_position);
+            target.get((2, 2))
+        };
+
+        // Screen space is misled by the Y-flip: it keeps the back-facing
+        // triangle and culls the front-facing one.
+        assert_eq!(render(&facing_camera, CullMethod::ScreenSpace), [0, 0, 0, 0]);
+        assert_eq!(render(&facing_away, CullMethod::ScreenSpace), [200, 100, 50, 255]);
+
+        // View-space normal cull sees through the projection and gets both right.
+        assert_eq!(render(&facing_camera, CullMethod::ViewSpaceNormal), [200, 100, 50, 255]);
+        assert_eq!(render(&facing_away, CullMethod::ViewSpaceNormal), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[cfg(feature = "legacy-barycentric")]
+    fn incremental_barycentric_weights_match_the_from_scratch_dot_product_solve() {
+        let p0 = glm::vec4(1.0, 1.0, 0.0, 1.0);
+        let p1 = glm::vec4(9.0, 2.0, 0.0, 1.0);
+        let p2 = glm::vec4(3.0, 8.0, 0.0, 1.0);
+        let setup = BarycentricSetup::new(&p0, &p1, &p2);
+
+        for y in 0..10 {
+            for x in 0..10 {
+                let (x, y) = (x as f32, y as f32);
+                let incremental = setup.weights_at(x, y);
+                let from_scratch = RenderContext::<'_, '_, Vertex, fn(&mut Vertex) -> glm::Vec4, fn(&Vertex) -> [u8; 4]>::barycentric_coordinates_from_scratch(
+                    &glm::vec4(x, y, 0.0, 0.0), &p0, &p1, &p2
+                );
+                assert!((incremental.0 - from_scratch.0).abs() < 1e-4);
+                assert!((incremental.1 - from_scratch.1).abs() < 1e-4);
+                assert!((incremental.2 - from_scratch.2).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "simd_barycentric")]
+    fn edges_batch4_is_bit_identical_to_four_scalar_weights_at_calls() {
+        let p0 = glm::vec4(1.0, 1.0, 0.0, 1.0);
+        let p1 = glm::vec4(9.0, 2.0, 0.0, 1.0);
+        let p2 = glm::vec4(3.0, 8.0, 0.0, 1.0);
+        let setup = BarycentricSetup::new(&p0, &p1, &p2);
+
+        for y in 0..10 {
+            let y = y as f32;
+            let batch = setup.edges_batch4(2.0, y);
+            for (lane, weights) in batch.iter().enumerate() {
+                let scalar = setup.weights_at(2.0 + lane as f32, y);
+                assert_eq!(*weights, scalar);
+            }
+        }
+    }
+
+    #[test]
+    fn is_inside_excludes_pixels_a_naive_bounding_box_span_would_have_included() {
+        // A tall, narrow sliver: near the apex the triangle's true width is
+        // a small fraction of a pixel, but a naive per-row span built from
+        // just the triangle's overall x-extent (ignoring how the two edges
+        // converge) still reports the whole base width as covered.
+        let p0 = glm::vec4(0.0, 0.0, 0.0, 1.0);
+        let p1 = glm::vec4(4.0, 0.0, 0.0, 1.0);
+        let p2 = glm::vec4(2.0, 20.0, 0.0, 1.0);
+        let bary = BarycentricSetup::new(&p0, &p1, &p2);
+
+        let y = 19.5; // just below the apex
+        let naive_x_span = 0..4; // the triangle's vertices span x in [0, 4]
+
+        let excluded: Vec<i32> = naive_x_span.clone()
+            .filter(|&x| !BarycentricSetup::is_inside(bary.weights_at(x as f32 + 0.5, y)))
+            .collect();
+
+        // Only a sliver right at the apex's x = 2 is actually inside; the
+        // rest of the naive span is excluded by the inside test.
+        assert!(excluded.len() >= naive_x_span.len() - 1);
+    }
+
+    #[test]
+    fn bgra_buffer_stores_logical_red_with_red_and_blue_channels_swapped() {
+        let mut target = TextureBuffer::new_with_format((1, 1), 4, PixelFormat::Bgra);
+        target.set((0, 0), &[255, 0, 0, 255]);
+
+        assert_eq!(*target.buffer, [0, 0, 255, 255]);
+        assert_eq!(target.get((0, 0)), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn resolve_averages_each_2x2_block_into_a_single_texel() {
+        let mut target = TextureBuffer::new((4, 2), 4);
+        target.set((0, 0), &[0, 0, 0, 255]);
+        target.set((1, 0), &[100, 0, 0, 255]);
+        target.set((0, 1), &[200, 0, 0, 255]);
+        target.set((1, 1), &[255, 0, 0, 255]);
+        target.set((2, 0), &[10, 10, 10, 10]);
+        target.set((3, 0), &[10, 10, 10, 10]);
+        target.set((2, 1), &[10, 10, 10, 10]);
+        target.set((3, 1), &[10, 10, 10, 10]);
+
+        let resolved = target.resolve(2);
+
+        assert_eq!(resolved.get((0, 0)), [138, 0, 0, 255]);
+        assert_eq!(resolved.get((1, 0)), [10, 10, 10, 10]);
+    }
+
+    #[test]
+    fn gaussian_resolve_weights_a_single_bright_texel_by_its_distance_from_the_block_center() {
+        // A single bright texel at (2, 2) in an otherwise black 6x6 buffer;
+        // resolving by 2 puts output pixel (1, 1)'s block center at (2.5, 2.5)
+        // with sigma = factor / 2 = 1.0, so the expected value is the bright
+        // texel's weight (relative to the full 4x4 sampled neighborhood)
+        // times its brightness.
+        let mut target = TextureBuffer::new((6, 6), 4);
+        target.set((2, 2), &[100, 100, 100, 100]);
+
+        let resolved = target.resolve_with_filter(2, ResolveFilter::Gaussian);
+
+        assert_eq!(resolved.get((1, 1)), [13, 13, 13, 13]);
+    }
+
+    #[test]
+    fn resolve_with_filter_box_matches_plain_resolve() {
+        let mut target = TextureBuffer::new((4, 2), 4);
+        target.set((0, 0), &[0, 0, 0, 255]);
+        target.set((1, 0), &[100, 0, 0, 255]);
+        target.set((0, 1), &[200, 0, 0, 255]);
+        target.set((1, 1), &[255, 0, 0, 255]);
+
+        let resolved = target.resolve_with_filter(2, ResolveFilter::Box);
+
+        assert_eq!(resolved.get((0, 0)), [138, 0, 0, 255]);
+    }
+
+    #[test]
+    fn double_buffer_front_reads_what_was_written_to_back_after_a_swap() {
+        let mut double_buffer = DoubleBuffer::new((1, 1), 4);
+
+        double_buffer.back_mut().set((0, 0), &[10, 20, 30, 255]);
+        assert_eq!(double_buffer.front().get((0, 0)), [0, 0, 0, 0]);
+
+        double_buffer.swap();
+
+        assert_eq!(double_buffer.front().get((0, 0)), [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn frustum_rejects_a_sphere_behind_the_camera_and_accepts_one_in_view() {
+        let camera = Camera::new(1.0, std::f32::consts::FRAC_PI_2, 0.1, 100.0);
+        let view_projection = camera.projection * camera.view;
+        let frustum = Frustum::from_view_projection(&view_projection);
+
+        let behind = BoundingSphere { center: glm::vec3(0.0, 0.0, 5.0), radius: 1.0 };
+        assert!(!frustum.intersects_sphere(&behind));
+
+        let in_view = BoundingSphere { center: glm::vec3(0.0, 0.0, -5.0), radius: 1.0 };
+        assert!(frustum.intersects_sphere(&in_view));
+    }
+
+    #[test]
+    fn depth_range_zero_to_one_maps_near_plane_to_zero_and_far_plane_to_one() {
+        let mut target = TextureBuffer::new((1, 1), 4);
+        let camera = Camera::new(1.0, std::f32::consts::FRAC_PI_2, 1.0, 100.0);
+        let render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 0, 0, 0]
+        ).with_depth_range(DepthRange::ZeroToOne);
+
+        let near = camera.projection * glm::vec4(0.0, 0.0, -1.0, 1.0);
+        let near = near / near.w;
+        let far = camera.projection * glm::vec4(0.0, 0.0, -100.0, 1.0);
+        let far = far / far.w;
+
+        assert!((render_context.transform_to_target_coordinates(&near).z - 0.0).abs() < 1e-5);
+        assert!((render_context.transform_to_target_coordinates(&far).z - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reverse_z_resolves_z_fighting_between_distant_quads_that_standard_z_cannot() {
+        // Two distinct, adjacent-bit-pattern NDC z values right at the far
+        // end of the depth range: as close together as two distinct depths
+        // can possibly be, standing in for two nearly-coplanar quads seen
+        // from far away. `ZeroToOne` rounds both to the same f32 depth, so
+        // the closer quad loses the depth test to the farther one even
+        // though it should win; `ReverseZeroToOne` keeps them distinct.
+        let farther_z: f32 = 0.999_899_9;
+        let closer_z: f32 = 0.999_899_86;
+
+        let quad = |z: f32| [
+            Vertex { position: glm::vec3(-1.0, -1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        let draw_both_quads = |depth_range: DepthRange, depth_func: DepthFunc, depth_clear: f32| {
+            let mut target = TextureBuffer::new((4, 4), 4);
+            let mut depth = DepthBuffer::new((4, 4));
+            depth.clear_depth(depth_clear);
+
+            RenderContext::new(
+                CullMode::None,
+                &mut target,
+                |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                |_: &Vertex| [255u8, 0, 0, 255]
+            ).with_depth_test(&mut depth, depth_func)
+             .with_depth_range(depth_range)
+             .draw_indexed_triangles(&indices, &quad(farther_z));
+
+            let mut render_context = RenderContext::new(
+                CullMode::None,
+                &mut target,
+                |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                |_: &Vertex| [0u8, 255, 0, 255]
+            ).with_depth_test(&mut depth, depth_func)
+             .with_depth_range(depth_range);
+            render_context.draw_indexed_triangles(&indices, &quad(closer_z));
+            render_context.stats().fragments_shaded
+        };
+
+        let standard_shaded = draw_both_quads(DepthRange::ZeroToOne, DepthFunc::Less, f32::INFINITY);
+        assert_eq!(standard_shaded, 0, "closer quad should lose to precision-collided depths under standard z");
+
+        let reverse_shaded = draw_both_quads(DepthRange::ReverseZeroToOne, DepthFunc::Greater, 0.0);
+        assert_eq!(reverse_shaded, 16, "closer quad should win once depths are distinguishable under reverse z");
+    }
+
+    #[test]
+    fn draw_flat_triangle_common_clamps_huge_projected_coordinates_without_hanging() {
+        // A degenerate, near-horizon triangle: one vertex is ~1e9 pixels off
+        // screen, which used to be able to turn the per-edge slope
+        // extrapolation into a row/column range spanning billions of
+        // entries. The call must return promptly and only ever touch pixels
+        // inside the 4x4 target.
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let v = Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+
+        let p0 = glm::vec4(0.0, -1e9, 0.0, 1.0);
+        let p1 = glm::vec4(1e9, 2.0, 0.0, 1.0);
+        let p2 = glm::vec4(0.0, 4.0, 0.0, 1.0);
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |vertex: &mut Vertex| glm::vec4(vertex.position.x, vertex.position.y, vertex.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        );
+        render_context.draw_flat_bottom_triangle(&p0, &p1, &p2, &v, &v, &v);
+
+        let touched = (0..4).flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|&(x, y)| target.get((x, y)) != [0, 0, 0, 0])
+            .count();
+        assert!(touched <= 16);
+    }
+
+    #[test]
+    fn sample_convention_changes_which_single_pixel_a_sub_pixel_aligned_quad_covers() {
+        // A 1x1 quad spanning screen coordinates [0.5, 1.5] x [0.5, 1.5]:
+        // under `TOP_LEFT` (sample at each pixel's integer origin) only the
+        // origin of pixel (1, 1) falls inside it; under `PIXEL_CENTER`
+        // (sample at `+0.5`) only the center of pixel (0, 0) does.
+        let v = Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let p0 = glm::vec4(0.5, 0.5, 0.0, 1.0);
+        let p1 = glm::vec4(1.5, 0.5, 0.0, 1.0);
+        let p2 = glm::vec4(1.5, 1.5, 0.0, 1.0);
+        let p3 = glm::vec4(0.5, 1.5, 0.0, 1.0);
+
+        let covered_pixels = |sample_convention: SampleConvention| {
+            let mut target = TextureBuffer::new((4, 4), 4);
+            {
+                let mut render_context = RenderContext::new(
+                    CullMode::None,
+                    &mut target,
+                    |vertex: &mut Vertex| glm::vec4(vertex.position.x, vertex.position.y, vertex.position.z, 1.0),
+                    |_: &Vertex| [255u8, 255, 255, 255]
+                ).with_sample_convention(sample_convention);
+                render_context.draw_triangle(&p0, &p1, &p2, &v, &v, &v);
+                render_context.draw_triangle(&p0, &p2, &p3, &v, &v, &v);
+            }
+            (0..4).flat_map(|y| (0..4).map(move |x| (x, y)))
+                .filter(|&(x, y)| target.get((x, y)) != [0, 0, 0, 0])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(covered_pixels(SampleConvention::TOP_LEFT), vec![(1, 1)]);
+        assert_eq!(covered_pixels(SampleConvention::PIXEL_CENTER), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn rasterize_triangle_yields_exactly_the_hand_computed_coverage_of_a_right_triangle() {
+        // A right triangle with its legs on the pixel grid: (0,0), (4,0),
+        // (0,4) in screen space. Under the top-left fill rule, the
+        // hypotenuse from (4,0) to (0,4) is neither a top nor a left edge
+        // (it slopes down-left, dy > 0 so it IS a top-left edge by this
+        // rasterizer's convention), so points exactly on it are included.
+        let p0 = glm::vec4(0.0, 0.0, 0.0, 1.0);
+        let p1 = glm::vec4(4.0, 0.0, 0.0, 1.0);
+        let p2 = glm::vec4(0.0, 4.0, 0.0, 1.0);
+        let v0 = Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let v1 = Vertex { position: glm::vec3(1.0, 0.0, 0.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let v2 = Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+
+        let fragments: Vec<(u32, u32)> = RenderContext::<'_, '_, Vertex,
+            fn(&mut Vertex) -> glm::Vec4, fn(&Vertex) -> [u8; 4]>::rasterize_triangle(
+            &p0, &p1, &p2, &v0, &v1, &v2, (8, 8), SampleConvention::TOP_LEFT)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+
+        let expected: Vec<(u32, u32)> = (0..4).flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|&(x, y)| x + y < 4)
+            .collect();
+
+        assert_eq!(fragments.len(), expected.len());
+        for point in &expected {
+            assert!(fragments.contains(point), "missing covered pixel {:?}", point);
+        }
+    }
+
+    #[test]
+    fn repeated_draws_reuse_the_same_scratch_buffer_capacity() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.5, -0.5, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.5, -0.5, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.5, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        );
+
+        render_context.draw_indexed_triangles(&indices, &vertices);
+        let capacity_after_first = render_context.scratch_vertices.capacity();
+        assert!(capacity_after_first >= vertices.len());
+
+        render_context.draw_indexed_triangles(&indices, &vertices);
+        assert_eq!(render_context.scratch_vertices.capacity(), capacity_after_first);
+    }
+
+    #[test]
+    fn draw_indexed_instanced_shades_as_many_pixels_as_drawing_each_instance_separately() {
+        fn count_shaded_pixels(target: &TextureBuffer<'_>, size: (u32, u32)) -> usize {
+            (0..size.1).flat_map(|y| (0..size.0).map(move |x| (x, y)))
+                .filter(|&p| target.get(p)[3] != 0)
+                .count()
+        }
+
+        let triangle_vertices = [
+            Vertex { position: glm::vec3(-0.1, -0.3, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.1, -0.3, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.3, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let triangle_indices = [0, 1, 2];
+        let size = (64, 32);
+
+        let mut baseline = TextureBuffer::new(size, 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut baseline,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 0, 0, 255]
+        ).draw_indexed_triangles(&triangle_indices, &triangle_vertices);
+        let single_triangle_pixels = count_shaded_pixels(&baseline, size);
+        assert!(single_triangle_pixels > 0);
+
+        let instance_count = 5;
+        let mut instanced = TextureBuffer::new(size, 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut instanced,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 0, 0, 255]
+        ).draw_indexed_instanced(&triangle_indices, &triangle_vertices, instance_count, |i| {
+            // Each step is a whole number of pixel widths (size.0 is 64, so
+            // one pixel is 2.0 / 64 = 0.03125 NDC units) so every instance
+            // reproduces the same sub-pixel coverage pattern as the
+            // baseline, just shifted — otherwise differing fractional pixel
+            // alignment would make the per-instance pixel counts diverge.
+            glm::translation(&glm::vec3((i as f32 - 2.0) * 0.25, 0.0, 0.0))
+        });
+
+        assert_eq!(count_shaded_pixels(&instanced, size), single_triangle_pixels * instance_count);
+    }
+
+    #[test]
+    fn viewport_renders_the_same_triangle_into_either_half_of_a_split_screen() {
+        let mut target = TextureBuffer::new((8, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.4, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.4, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        ).with_viewport(Viewport { x: 0, y: 0, width: 4, height: 4 })
+         .draw_indexed_triangles(&indices, &vertices);
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        ).with_viewport(Viewport { x: 4, y: 0, width: 4, height: 4 })
+         .draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((2, 1)), [255, 255, 255, 255]);
+        assert_eq!(target.get((6, 1)), [255, 255, 255, 255]);
+        for y in 0..4 {
+            assert_eq!(target.get((3, y)), [0, 0, 0, 0]);
+            assert_eq!(target.get((4, y)), [0, 0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn scissor_clips_rasterization_to_the_intersecting_rect() {
+        let mut target = TextureBuffer::new((256, 256), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        );
+        render_context.set_scissor(Some((100, 100, 50, 50)));
+        render_context.draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((125, 125)), [255, 255, 255, 255]);
+        assert_eq!(target.get((99, 125)), [0, 0, 0, 0]);
+        assert_eq!(target.get((150, 125)), [0, 0, 0, 0]);
+        assert_eq!(target.get((125, 99)), [0, 0, 0, 0]);
+        assert_eq!(target.get((125, 150)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn clip_plane_discards_fragments_on_its_negative_side() {
+        let mut target = TextureBuffer::new((256, 256), 4);
+        // Oversized so the visible half of the buffer is fully covered once
+        // `clip_polygon` clips it to the viewport, same trick as the scissor
+        // test above.
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        // Keeps only `position.x >= 0`, i.e. the right half of the buffer
+        // once NDC x in [-1, 1] maps across its width.
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        ).with_clip_plane(glm::vec4(1.0, 0.0, 0.0, 0.0))
+         .draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(target.get((200, 128)), [255, 255, 255, 255]);
+        assert_eq!(target.get((50, 128)), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn alpha_blend_mixes_source_and_destination_colors() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                target.set((x, y), &[0, 0, 255, 255]);
+            }
+        }
+
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 0, 0, 128]
+        ).with_blend_mode(BlendMode::AlphaBlend);
+        render_context.draw_indexed_triangles(&indices, &vertices);
+
+        // 50%-alpha red over a blue background blends to a purple center pixel.
+        assert_eq!(target.get((2, 2)), [128, 0, 127, 191]);
+    }
+
+    #[test]
+    fn lambert_lighting_modulates_base_color_by_the_interpolated_normal() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+        let light_dir = glm::normalize(&glm::vec3(0.0, 0.0, 1.0));
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| {
+                let normal = glm::normalize(&v.normal);
+                let diffuse = f32::max(glm::dot(&normal, &light_dir), 0.0);
+                [(255.0 * diffuse) as u8, 0, 0, 255]
+            }
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        // The normal faces the light head-on, so the triangle's interior is lit at full intensity.
+        assert_eq!(target.get((2, 2)), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn gouraud_vertex_shader_bakes_lighting_into_the_interpolated_color() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            GouraudVertex { position: glm::vec3(-0.9, -0.9, 0.0), color: glm::vec3(0.0, 0.0, 1.0) },
+            GouraudVertex { position: glm::vec3(0.9, -0.9, 0.0), color: glm::vec3(0.0, 0.0, 1.0) },
+            GouraudVertex { position: glm::vec3(0.0, 0.9, 0.0), color: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+        let light_dir = glm::normalize(&glm::vec3(0.0, 0.0, 1.0));
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut GouraudVertex| {
+                let p = v.position;
+                let normal = glm::normalize(&v.color);
+                let diffuse = f32::max(glm::dot(&normal, &light_dir), 0.0);
+                v.color = glm::vec3(1.0, 0.0, 0.0) * diffuse;
+                glm::vec4(p.x, p.y, p.z, 1.0)
+            },
+            |v: &GouraudVertex| [
+                (v.color.x.clamp(0.0, 1.0) * 255.0) as u8,
+                (v.color.y.clamp(0.0, 1.0) * 255.0) as u8,
+                (v.color.z.clamp(0.0, 1.0) * 255.0) as u8,
+                255
+            ]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        // The baked-in color, not a normal, is what gets interpolated here.
+        assert_eq!(target.get((2, 2)), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn colored_textured_vertex_multiplies_the_sampled_texel_by_the_interpolated_color() {
+        let texture = Texture::solid(2, 2, [200, 100, 50, 255]);
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let tint = glm::vec3(0.5, 1.0, 0.2);
+        let vertices = [
+            ColoredTexturedVertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), color: tint },
+            ColoredTexturedVertex { position: glm::vec3(1.0, -1.0, 0.0), uv: glm::vec2(1.0, 0.0), color: tint },
+            ColoredTexturedVertex { position: glm::vec3(1.0, 1.0, 0.0), uv: glm::vec2(1.0, 1.0), color: tint },
+            ColoredTexturedVertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 1.0), color: tint }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut ColoredTexturedVertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &ColoredTexturedVertex| {
+                let texel = texture.sample(v.uv);
+                [
+                    (texel[0] as f32 * v.color.x).round() as u8,
+                    (texel[1] as f32 * v.color.y).round() as u8,
+                    (texel[2] as f32 * v.color.z).round() as u8,
+                    texel[3]
+                ]
+            }
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        // The texture is `solid`, so every fragment samples the same texel;
+        // the tint is uniform too, so the whole quad should equal texel*color.
+        assert_eq!(target.get((2, 2)), [100, 100, 10, 255]);
+    }
+
+    #[test]
+    fn rgb_triangle_center_pixel_is_the_average_of_its_three_corner_colors() {
+        // The classic RGB triangle: no lighting, `GouraudVertex::color` is
+        // just passed straight through and interpolated by the rasterizer,
+        // which doubles as a correctness check for barycentric interpolation.
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            GouraudVertex { position: glm::vec3(0.0, -0.9, 0.0), color: glm::vec3(1.0, 0.0, 0.0) },
+            GouraudVertex { position: glm::vec3(-0.9, 0.45, 0.0), color: glm::vec3(0.0, 1.0, 0.0) },
+            GouraudVertex { position: glm::vec3(0.9, 0.45, 0.0), color: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut GouraudVertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &GouraudVertex| [
+                (v.color.x.clamp(0.0, 1.0) * 255.0).round() as u8,
+                (v.color.y.clamp(0.0, 1.0) * 255.0).round() as u8,
+                (v.color.z.clamp(0.0, 1.0) * 255.0).round() as u8,
+                255
+            ]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        // The vertex colors sum to (1, 1, 1) and the triangle's centroid sits
+        // at the origin, which this 4x4 target maps to pixel (2, 2); the
+        // average of the three corners is thus an even gray.
+        let average = 255 / 3;
+        assert_eq!(target.get((2, 2)), [average, average, average, 255]);
+    }
+
+    #[test]
+    fn flat_shade_model_paints_every_covered_pixel_with_the_provoking_vertex_color() {
+        // Same RGB triangle as above, but in `Flat` mode every pixel should
+        // come out pure red (`v0`'s color) instead of being interpolated.
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            GouraudVertex { position: glm::vec3(0.0, -0.9, 0.0), color: glm::vec3(1.0, 0.0, 0.0) },
+            GouraudVertex { position: glm::vec3(-0.9, 0.45, 0.0), color: glm::vec3(0.0, 1.0, 0.0) },
+            GouraudVertex { position: glm::vec3(0.9, 0.45, 0.0), color: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut GouraudVertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &GouraudVertex| [
+                (v.color.x.clamp(0.0, 1.0) * 255.0).round() as u8,
+                (v.color.y.clamp(0.0, 1.0) * 255.0).round() as u8,
+                (v.color.z.clamp(0.0, 1.0) * 255.0).round() as u8,
+                255
+            ]
+        ).with_shade_model(ShadeModel::Flat)
+        .draw_indexed_triangles(&indices, &vertices);
+
+        // The centroid at pixel (2, 2) is covered (see the smooth-shaded
+        // test above); every covered pixel across the whole triangle should
+        // be pure red rather than a per-pixel interpolated blend.
+        assert_eq!(target.get((2, 2)), [255, 0, 0, 255]);
+        let mut covered_pixels = 0;
+        for y in 0..4 {
+            for x in 0..4 {
+                let pixel = target.get((x, y));
+                if pixel != [0, 0, 0, 0] {
+                    assert_eq!(pixel, [255, 0, 0, 255]);
+                    covered_pixels += 1;
+                }
+            }
+        }
+        assert!(covered_pixels > 1);
+    }
+
+    #[test]
+    fn debug_output_depth_paints_a_near_fragment_brighter_than_a_far_one() {
+        // A near quad (z = -0.5, left half) and a far quad (z = 0.9, right
+        // half), both full NDC z far outside the [0, 1] range a real pixel
+        // shader would ever see; `DebugOutput::Depth` replaces the pixel
+        // shader entirely, so its own (unused) output can be anything.
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -1.0, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, -0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -1.0, 0.9), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.9), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.9), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.9), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+
+        let mut target = TextureBuffer::new((8, 8), 4);
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [0u8, 0, 0, 0]
+        ).with_debug_output(DebugOutput::Depth { near: 0.1, far: 10.0 })
+         .draw_indexed_triangles(&indices, &vertices);
+
+        let near_value = target.get((2, 4))[0];
+        let far_value = target.get((6, 4))[0];
+        assert!(near_value > far_value,
+            "expected the near fragment ({}) to be brighter than the far one ({})", near_value, far_value);
+    }
+
+    #[test]
+    fn overdraw_tracking_counts_two_for_the_overlap_of_two_quads_and_one_elsewhere() {
+        // Quad A spans NDC x in [-1, 0] (pixel columns 0-1); quad B spans
+        // [-0.5, 1] (pixel columns 1-3). Column 1 is covered by both.
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.5, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-0.5, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let mut overdraw = OverdrawBuffer::new((4, 4));
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [200u8, 60, 30, 255]
+        ).with_overdraw_tracking(&mut overdraw)
+         .draw_indexed_triangles(&indices, &vertices);
+
+        assert_eq!(overdraw.get((1, 2)), 2);
+        assert_eq!(overdraw.get((0, 2)), 1);
+        assert_eq!(overdraw.get((3, 2)), 1);
+    }
+
+    #[test]
+    fn quad_split_into_two_triangles_covers_every_pixel_exactly_once() {
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        // Split along the diagonal shared by both triangles: if that edge were
+        // double-drawn or left empty, the additive blend below would reveal it.
+        let indices = [0, 1, 2, 0, 2, 3];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [1u8, 0, 0, 0]
+        ).with_blend_mode(BlendMode::Additive).draw_indexed_triangles(&indices, &vertices);
+
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(target.get((x, y))[0], 1, "pixel ({x}, {y}) was drawn {} times", target.get((x, y))[0]);
+            }
+        }
+    }
+
+    #[test]
+    fn generate_grid_lines_produces_the_expected_segment_count_for_extent_and_spacing() {
+        let (vertices, indices) = generate_grid_lines(1.0, 2.0);
+
+        // Lines run from -extent to extent in steps of spacing on each axis,
+        // so there are `2 * (extent / spacing) + 1` lines per axis, times
+        // two axes, each contributing one segment (two indices).
+        let lines_per_axis = 2 * (2.0f32 / 1.0).floor() as usize + 1;
+        let expected_segments = lines_per_axis * 2;
+
+        assert_eq!(indices.len(), expected_segments * 2);
+        assert_eq!(vertices.len(), expected_segments * 2);
+    }
+
+    #[test]
+    fn generate_normal_lines_yields_a_segment_from_position_to_position_plus_normal_times_length() {
+        let vertices = [
+            Vertex { position: glm::vec3(1.0, 2.0, 3.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 1.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0, 0.0, 0.5), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(1.0, 0.0, 0.0) }
+        ];
+
+        let (line_vertices, indices) = generate_normal_lines(&vertices, 0.5);
+
+        assert_eq!(indices.len(), 4);
+        assert_eq!(line_vertices[0].position, glm::vec3(1.0, 2.0, 3.0));
+        assert_eq!(line_vertices[1].position, glm::vec3(1.0, 2.5, 3.0));
+        assert_eq!(line_vertices[2].position, glm::vec3(-1.0, 0.0, 0.5));
+        assert_eq!(line_vertices[3].position, glm::vec3(-0.5, 0.0, 0.5));
+    }
+
+    #[test]
+    fn mrt_render_context_writes_each_target_with_its_own_channel_of_the_pixel_shader_output() {
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        let mut albedo = TextureBuffer::new((2, 2), 4);
+        let mut normal = TextureBuffer::new((2, 2), 4);
+        MrtRenderContext::new(
+            CullMode::None,
+            [&mut albedo, &mut normal],
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [[200, 100, 50, 255], [0, 0, 255, 255]]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        // The triangle covers the whole 2x2 target, so every pixel of every
+        // attachment should carry that attachment's own color.
+        for x in 0..2 {
+            for y in 0..2 {
+                assert_eq!(albedo.get((x, y)), [200, 100, 50, 255]);
+                assert_eq!(normal.get((x, y)), [0, 0, 255, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn material_render_context_reuses_shaders_across_different_uniform_sets() {
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        // The same, non-capturing shader closures are reused unchanged for
+        // both draws below; only the uniforms passed to `draw_indexed_triangles`
+        // differ, standing in for e.g. a different material's MVP/tint.
+        let vertex_shader = |_: &[u8; 4], v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0);
+        let pixel_shader = |tint: &[u8; 4], _: &Vertex| *tint;
+
+        let mut target = TextureBuffer::new((2, 2), 4);
+        let mut render_context = MaterialRenderContext::new(CullMode::None, &mut target, vertex_shader, pixel_shader);
+        render_context.draw_indexed_triangles(&indices, &vertices, &[200, 100, 50, 255]);
+        assert_eq!(render_context.target.get((0, 0)), [200, 100, 50, 255]);
+
+        render_context.draw_indexed_triangles(&indices, &vertices, &[10, 20, 30, 255]);
+        assert_eq!(render_context.target.get((0, 0)), [10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn quad_render_context_reports_ddx_matching_the_per_pixel_uv_step_of_a_linear_uv_gradient() {
+        let vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(3.0, -1.0, 0.0), uv: glm::vec2(2.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 3.0, 0.0), uv: glm::vec2(0.0, 2.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+        let mut target = TextureBuffer::new((4, 4), 4);
+        let observed_ddx = std::sync::atomic::AtomicU32::new(0.0f32.to_bits());
+
+        QuadRenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex, d: &Derivatives<Vertex>| {
+                observed_ddx.store(d.ddx.uv.x.to_bits(), Ordering::Relaxed);
+                [0, 0, 0, 255]
+            }
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        // A right triangle with legs of 8 screen pixels (NDC x/y spanning
+        // -1..3, twice the 4-pixel target) and UV.x running from 0 to 2 over
+        // that leg interpolates UV.x linearly at 2/8 = 0.25 per pixel of
+        // screen x — exactly the ddx every shaded fragment should report.
+        let observed_ddx = f32::from_bits(observed_ddx.load(Ordering::Relaxed));
+        assert!((observed_ddx - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn detect_front_face_matches_the_winding_a_small_tetrahedron_was_authored_with() {
+        let vertices = [
+            Vertex { position: glm::vec3(0.0, 0.0, 3.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(1.0, -1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) },
+            Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) }
+        ];
+        // All four faces authored counter-clockwise as seen from outside.
+        let counter_clockwise_indices = [0, 1, 2, 0, 2, 3, 0, 3, 1, 1, 3, 2];
+
+        assert_eq!(detect_front_face(&vertices, &counter_clockwise_indices), FrontFace::CounterClockwise);
+
+        // Swapping the last two indices of every face reverses every
+        // winding, so the detected front face should flip too.
+        let clockwise_indices: Vec<usize> = counter_clockwise_indices
+            .chunks_exact(3)
+            .flat_map(|f| [f[0], f[2], f[1]])
+            .collect();
+
+        assert_eq!(detect_front_face(&vertices, &clockwise_indices), FrontFace::Clockwise);
+    }
+
+    #[test]
+    fn parse_obj_deduplicates_shared_corners_of_a_two_triangle_quad() {
+        let obj = "\
+            v -1.0 -1.0 0.0\n\
+            v 1.0 -1.0 0.0\n\
+            v 1.0 1.0 0.0\n\
+            v -1.0 1.0 0.0\n\
+            vt 0.0 0.0\n\
+            vt 1.0 0.0\n\
+            vt 1.0 1.0\n\
+            vt 0.0 1.0\n\
+            vn 0.0 0.0 1.0\n\
+            f 1/1/1 2/2/1 3/3/1\n\
+            f 1/1/1 3/3/1 4/4/1\n";
+
+        let (vertices, indices) = parse_obj(obj).unwrap();
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 6);
+        assert_eq!(vertices[0].position, glm::vec3(-1.0, -1.0, 0.0));
+        assert_eq!(vertices[0].normal, glm::vec3(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn parse_obj_fills_in_zero_uv_and_normal_when_a_face_omits_them() {
+        let obj = "\
+            v 0.0 0.0 0.0\n\
+            v 1.0 0.0 0.0\n\
+            v 0.0 1.0 0.0\n\
+            f 1 2 3\n";
+
+        let (vertices, indices) = parse_obj(obj).unwrap();
+
+        assert_eq!(vertices.len(), 3);
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(vertices[0].uv, glm::vec2(0.0, 0.0));
+        assert_eq!(vertices[0].normal, glm::vec3(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn load_obj_reads_a_two_triangle_quad_from_disk() {
+        let obj = "\
+            v -1.0 -1.0 0.0\n\
+            v 1.0 -1.0 0.0\n\
+            v 1.0 1.0 0.0\n\
+            v -1.0 1.0 0.0\n\
+            vt 0.0 0.0\n\
+            vt 1.0 0.0\n\
+            vt 1.0 1.0\n\
+            vt 0.0 1.0\n\
+            vn 0.0 0.0 1.0\n\
+            f 1/1/1 2/2/1 3/3/1\n\
+            f 1/1/1 3/3/1 4/4/1\n";
+
+        let path = std::env::temp_dir().join("load_obj_round_trip_test.obj");
+        std::fs::write(&path, obj).unwrap();
+        let (vertices, indices) = load_obj(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 6);
+    }
+
+    #[test]
+    fn load_obj_detecting_winding_reports_the_winding_a_tetrahedron_was_authored_with() {
+        // Same tetrahedron and winding as
+        // `detect_front_face_matches_the_winding_a_small_tetrahedron_was_authored_with`,
+        // all four faces counter-clockwise as seen from outside.
+        let obj = "\
+            v 0.0 0.0 3.0\n\
+            v -1.0 -1.0 0.0\n\
+            v 1.0 -1.0 0.0\n\
+            v 0.0 1.0 0.0\n\
+            f 1 2 3\n\
+            f 1 3 4\n\
+            f 1 4 2\n\
+            f 2 4 3\n";
+
+        let path = std::env::temp_dir().join("load_obj_detecting_winding_test.obj");
+        std::fs::write(&path, obj).unwrap();
+        let (vertices, indices, front_face) = load_obj_detecting_winding(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(vertices.len(), 4);
+        assert_eq!(indices.len(), 12);
+        assert_eq!(front_face, FrontFace::CounterClockwise);
+    }
+
+    #[test]
+    fn vertices_from_interleaved_buffer_reads_position_uv_and_normal_per_record() {
+        let mut buffer = Vec::new();
+        let mut push_record = |position: [f32; 3], uv: [f32; 2], normal: [f32; 3]| {
+            for f in position { buffer.extend_from_slice(&f.to_le_bytes()); }
+            for f in uv { buffer.extend_from_slice(&f.to_le_bytes()); }
+            for f in normal { buffer.extend_from_slice(&f.to_le_bytes()); }
+        };
+        push_record([-1.0, -1.0, 0.0], [0.0, 0.0], [0.0, 0.0, 1.0]);
+        push_record([1.0, -1.0, 0.0], [1.0, 0.0], [0.0, 0.0, 1.0]);
+
+        let vertices = vertices_from_interleaved_buffer(&buffer, &VertexLayout::INTERLEAVED_POSITION_UV_NORMAL).unwrap();
+
+        assert_eq!(vertices.len(), 2);
+        assert_eq!(vertices[0].position, glm::vec3(-1.0, -1.0, 0.0));
+        assert_eq!(vertices[0].uv, glm::vec2(0.0, 0.0));
+        assert_eq!(vertices[0].normal, glm::vec3(0.0, 0.0, 1.0));
+        assert_eq!(vertices[1].position, glm::vec3(1.0, -1.0, 0.0));
+        assert_eq!(vertices[1].uv, glm::vec2(1.0, 0.0));
+        assert_eq!(vertices[1].normal, glm::vec3(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn vertices_from_interleaved_buffer_rejects_a_length_that_is_not_a_multiple_of_the_stride() {
+        let buffer = vec![0u8; VertexLayout::INTERLEAVED_POSITION_UV_NORMAL.stride + 1];
+
+        let result = vertices_from_interleaved_buffer(&buffer, &VertexLayout::INTERLEAVED_POSITION_UV_NORMAL);
+
+        assert!(matches!(result, Err(LoadError::Parse(_))));
+    }
+
+    #[test]
+    fn camera_controller_faces_negative_z_at_zero_yaw_and_pitch() {
+        let controller = CameraController::new(glm::vec3(0.0, 0.0, 0.0), 0.0, 0.0);
+
+        let forward = controller.forward();
+        assert!((forward - glm::vec3(0.0, 0.0, -1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn camera_controller_moves_along_forward_and_right_on_keyboard_input() {
+        let mut controller = CameraController::new(glm::vec3(0.0, 0.0, 0.0), 0.0, 0.0);
+
+        controller.process_keyboard(true, false, false, true, 1.0);
+
+        // Forward is -Z and right is +X at yaw 0, pitch 0.
+        assert!((controller.position - glm::vec3(1.0, 0.0, -1.0)).norm() < 1e-6);
+    }
+
+    #[test]
+    fn camera_controller_clamps_pitch_to_avoid_flipping_past_vertical() {
+        let mut controller = CameraController::new(glm::vec3(0.0, 0.0, 0.0), 0.0, 0.0);
+
+        controller.process_mouse(0.0, -1000.0, 1.0);
+
+        assert!(controller.pitch <= std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn orbit_camera_eye_position_matches_the_hand_computed_spherical_offset() {
+        let orbit_camera = OrbitCamera::new(glm::vec3(1.0, 2.0, 3.0), 5.0, std::f32::consts::FRAC_PI_2, 0.0);
+
+        let eye = orbit_camera.eye();
+
+        // At yaw = pi/2, pitch = 0, the offset is (distance, 0, 0).
+        assert!((eye - glm::vec3(6.0, 2.0, 3.0)).norm() < 1e-5);
+    }
+
+    #[test]
+    fn compute_aabb_returns_the_min_and_max_corner_of_known_vertices() {
+        let make_vertex = |x: f32, y: f32, z: f32| Vertex { position: glm::vec3(x, y, z), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 0.0) };
+        let vertices = [
+            make_vertex(-1.0, 2.0, 0.0),
+            make_vertex(3.0, -4.0, 5.0),
+            make_vertex(0.0, 0.0, -2.0)
+        ];
+
+        let (min, max) = compute_aabb(&vertices);
+
+        assert_eq!(min, glm::vec3(-1.0, -4.0, -2.0));
+        assert_eq!(max, glm::vec3(3.0, 2.0, 5.0));
+    }
+
+    #[test]
+    fn orbit_camera_framing_targets_the_aabb_center_and_fits_its_bounding_sphere_in_the_fov() {
+        let min = glm::vec3(-1.0, -1.0, -1.0);
+        let max = glm::vec3(3.0, 3.0, 3.0);
+        let fovy = std::f32::consts::FRAC_PI_2;
+
+        let orbit_camera = OrbitCamera::framing(min, max, fovy);
+
+        // Center is (1, 1, 1); radius is half the diagonal length, and at a
+        // 90-degree fov (half-angle pi/4, sin = sqrt(2)/2) the distance is
+        // radius * sqrt(2).
+        let expected_radius = glm::length(&(max - min)) * 0.5;
+        assert!((orbit_camera.target - glm::vec3(1.0, 1.0, 1.0)).norm() < 1e-5);
+        assert!((orbit_camera.distance - expected_radius * std::f32::consts::SQRT_2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn blinn_phong_specular_peaks_when_the_half_vector_aligns_with_the_normal() {
+        let normal = glm::normalize(&glm::vec3(0.0, 0.0, 1.0));
+        // View and light directions symmetric about the normal, so their
+        // half-vector is exactly the normal itself.
+        let view_dir = glm::normalize(&glm::vec3(0.5, 0.0, 1.0));
+        let light_dir = glm::normalize(&glm::vec3(-0.5, 0.0, 1.0));
+
+        let aligned = blinn_phong_specular(normal, view_dir, light_dir, 32.0);
+        assert!((aligned - 1.0).abs() < 1e-5);
+
+        // Tilting the light away from that symmetric case can only lower the
+        // term, since the half-vector no longer aligns with the normal.
+        let tilted_light_dir = glm::normalize(&glm::vec3(-0.9, 0.3, 0.4));
+        let tilted = blinn_phong_specular(normal, view_dir, tilted_light_dir, 32.0);
+        assert!(tilted < aligned);
+
+        // A higher shininess exponent sharpens the falloff away from the peak.
+        let sharper = blinn_phong_specular(normal, view_dir, tilted_light_dir, 128.0);
+        assert!(sharper < tilted);
+    }
+
+    #[test]
+    fn camera_mvp_matches_the_manual_projection_view_model_product() {
+        let mut camera = Camera::new(16.0 / 9.0, std::f32::consts::FRAC_PI_4, 0.1, 100.0);
+        camera.look_at(glm::vec3(1.0, 2.0, 3.0), glm::vec3(0.0, 0.0, 0.0), glm::vec3(0.0, 1.0, 0.0));
+        let model = glm::translation(&glm::vec3(4.0, 5.0, 6.0)) * glm::scaling(&glm::vec3(2.0, 2.0, 2.0));
+
+        let expected = camera.projection * camera.view * model;
+
+        assert_eq!(camera.view_projection(), camera.projection * camera.view);
+        assert_eq!(camera.mvp(&model), expected);
+    }
+
+    #[test]
+    fn camera_set_aspect_rebuilds_the_perspective_projection_but_not_an_orthographic_one() {
+        let mut camera = Camera::new(1.0, std::f32::consts::FRAC_PI_4, 0.1, 100.0);
+        camera.set_aspect(2.0);
+        assert_eq!(camera.projection, glm::perspective(2.0, std::f32::consts::FRAC_PI_4, 0.1, 100.0));
+
+        let mut ortho_camera = Camera::orthographic(-1.0, 1.0, -1.0, 1.0, 0.1, 100.0);
+        let unchanged = ortho_camera.projection;
+        ortho_camera.set_aspect(2.0);
+        assert_eq!(ortho_camera.projection, unchanged);
+    }
+
+    #[test]
+    fn tiled_backend_shades_the_same_triangle_interior_as_scanline() {
+        let vertices = [
+            Vertex { position: glm::vec3(-0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.9, -0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.9, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+        let vertex_shader = |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0);
+        let pixel_shader = |_: &Vertex| [200u8, 60, 30, 255];
+
+        let mut scanline_target = TextureBuffer::new((8, 8), 4);
+        RenderContext::new(CullMode::None, &mut scanline_target, vertex_shader, pixel_shader)
+            .draw_indexed_triangles(&indices, &vertices);
+
+        let mut tiled_target = TextureBuffer::new((8, 8), 4);
+        RenderContext::new(CullMode::None, &mut tiled_target, vertex_shader, pixel_shader)
+            .with_raster_backend(RasterBackend::Tiled { tile_size: 3 })
+            .draw_indexed_triangles(&indices, &vertices);
+
+        // The two backends use different edge-fill rules at the triangle's
+        // boundary, but must agree deep in its interior.
+        assert_eq!(scanline_target.get((4, 4)), [200, 60, 30, 255]);
+        assert_eq!(tiled_target.get((4, 4)), [200, 60, 30, 255]);
+    }
+
+    #[test]
+    fn edge_function_backend_covers_the_same_pixels_as_scanline_for_randomized_triangles() {
+        // A small deterministic LCG rather than pulling in a `rand`
+        // dependency just for this one property test.
+        let mut state = 0x1234_5678u32;
+        let mut next_unit = || {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1u32 << 24) as f32
+        };
+
+        for _ in 0..20 {
+            let mut make_vertex = || {
+                let x = next_unit() * 2.0 - 1.0;
+                let y = next_unit() * 2.0 - 1.0;
+                Vertex { position: glm::vec3(x, y, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+            };
+            let vertices = [make_vertex(), make_vertex(), make_vertex()];
+            let indices = [0, 1, 2];
+            let vertex_shader = |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0);
+            let pixel_shader = |_: &Vertex| [200u8, 60, 30, 255];
+
+            let mut scanline_target = TextureBuffer::new((8, 8), 4);
+            RenderContext::new(CullMode::None, &mut scanline_target, vertex_shader, pixel_shader)
+                .draw_indexed_triangles(&indices, &vertices);
+
+            let mut edge_function_target = TextureBuffer::new((8, 8), 4);
+            RenderContext::new(CullMode::None, &mut edge_function_target, vertex_shader, pixel_shader)
+                .with_raster_backend(RasterBackend::EdgeFunction)
+                .draw_indexed_triangles(&indices, &vertices);
+
+            // Both use `covers_with_top_left_rule`, so unlike `Tiled` they
+            // must agree pixel-for-pixel, all the way to the triangle's edges.
+            assert_eq!(*scanline_target.buffer, *edge_function_target.buffer);
+        }
+    }
+
+    #[test]
+    fn wrap_mode_maps_out_of_range_uvs_as_expected() {
+        assert_eq!(WrapMode::Clamp.apply(-0.25), 0.0);
+        assert_eq!(WrapMode::Clamp.apply(1.25), 1.0);
+        assert_eq!(WrapMode::Clamp.apply(2.5), 1.0);
+
+        assert!((WrapMode::Repeat.apply(-0.25) - 0.75).abs() < 1e-6);
+        assert!((WrapMode::Repeat.apply(1.25) - 0.25).abs() < 1e-6);
+        assert!((WrapMode::Repeat.apply(2.5) - 0.5).abs() < 1e-6);
+
+        assert!((WrapMode::Mirror.apply(-0.25) - 0.25).abs() < 1e-6);
+        assert!((WrapMode::Mirror.apply(1.25) - 0.75).abs() < 1e-6);
+        assert!((WrapMode::Mirror.apply(2.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clip_polygon_splits_near_plane_straddling_triangle_into_a_quad() {
+        let positions = [
+            glm::vec4(0.0, 0.0, -2.0, 1.0),
+            glm::vec4(1.0, 0.0, 1.0, 1.0),
+            glm::vec4(-1.0, 0.0, 1.0, 1.0)
+        ];
+        let vertices = [
+            Vertex { position: glm::vec3(0.0, 0.0, -2.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 0.0, 1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 0.0, 1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+
+        let (clipped_positions, clipped_vertices) =
+            NoopRenderContext::clip_polygon(&positions, &vertices);
+
+        assert_eq!(clipped_positions.len(), 4);
+        assert_eq!(clipped_vertices.len(), 4);
+        for p in &clipped_positions {
+            assert!(p.z + p.w >= -1e-6);
+        }
+
+        assert!((clipped_vertices[0].uv.y - 1.0 / 3.0).abs() < 1e-6);
+        assert!((clipped_vertices[1].uv.x - 1.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn clip_polygon_keeps_fully_visible_triangle_unchanged() {
+        let positions = [
+            glm::vec4(0.0, 0.0, 1.0, 1.0),
+            glm::vec4(1.0, 0.0, 1.0, 1.0),
+            glm::vec4(-1.0, 0.0, 1.0, 1.0)
+        ];
+        let vertices = [
+            Vertex { position: glm::vec3(0.0, 0.0, 1.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(1.0, 0.0, 1.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0, 0.0, 1.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+
+        let (clipped_positions, _) = NoopRenderContext::clip_polygon(&positions, &vertices);
+
+        assert_eq!(clipped_positions, positions.to_vec());
+    }
+
+    #[test]
+    fn clip_polygon_handles_a_triangle_straddling_the_left_and_top_planes() {
+        let positions = [
+            glm::vec4(-2.0, 0.0, 0.0, 1.0),
+            glm::vec4(0.0, 1.6, 0.0, 1.0),
+            glm::vec4(0.0, -0.5, 0.0, 1.0)
+        ];
+        let vertices = [
+            Vertex { position: glm::vec3(-2.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 1.6, 0.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, -0.5, 0.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+
+        let (clipped_positions, clipped_vertices) = NoopRenderContext::clip_polygon(&positions, &vertices);
+
+        assert_eq!(clipped_positions.len(), 5);
+        for p in &clipped_positions {
+            assert!(p.w + p.x >= -1e-6 && p.w - p.x >= -1e-6);
+            assert!(p.w + p.y >= -1e-6 && p.w - p.y >= -1e-6);
+        }
+
+        // The left-plane crossing between the first and second source vertices.
+        assert!((clipped_vertices[1].uv.x - 0.5).abs() < 1e-6);
+        assert!((clipped_vertices[1].uv.y - 0.0).abs() < 1e-6);
+
+        // The top-plane crossing on the edge leaving the second source vertex.
+        assert!((clipped_vertices[2].uv.x - 0.625).abs() < 1e-6);
+        assert!((clipped_vertices[2].uv.y - 0.0).abs() < 1e-6);
+
+        // The top-plane crossing on the edge entering the third source vertex.
+        assert!((clipped_vertices[3].uv.x - 0.714286).abs() < 1e-5);
+        assert!((clipped_vertices[3].uv.y - 0.285714).abs() < 1e-5);
+    }
+
+    #[test]
+    fn draw_line_aa_splits_shallow_line_coverage_between_the_two_straddling_rows() {
+        let mut target = TextureBuffer::new((8, 4), 4);
+        let v = Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+
+        let mut render_context = RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |vertex: &mut Vertex| glm::vec4(vertex.position.x, vertex.position.y, vertex.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        ).with_anti_alias(AntiAlias::Coverage);
+
+        render_context.draw_line_aa(1.0, 0.5, v, 5.0, 0.5, v);
+
+        for x in 1..5 {
+            let upper = target.get((x, 0))[3] as f32;
+            let lower = target.get((x, 1))[3] as f32;
+            assert!(upper > 0.0 && lower > 0.0, "expected both straddling rows covered at x={}", x);
+            assert!((upper + lower - 255.0).abs() <= 2.0, "x={} upper={} lower={}", x, upper, lower);
+        }
+    }
+
+    #[test]
+    fn clock_tick_reports_elapsed_seconds_since_the_previous_tick() {
+        let mut clock = Clock::new();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let dt = clock.tick();
+        assert!(dt >= 0.02, "expected at least 20ms elapsed, got {}s", dt);
+        assert!(dt < 1.0, "expected a small elapsed time, got {}s", dt);
+    }
+
+    #[test]
+    fn fps_counter_average_ms_reflects_the_fed_frame_times() {
+        let mut fps_counter = FpsCounter::new();
+        for ms in [10, 20, 30] {
+            fps_counter.record_frame_time(std::time::Duration::from_millis(ms));
+        }
+
+        assert!((fps_counter.last_frame_ms() - 30.0).abs() < 1e-3);
+        assert!((fps_counter.average_ms() - 20.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn collinear_vertices_write_no_pixels_and_produce_no_nan() {
+        let mut target = TextureBuffer::new((8, 8), 4);
+        let vertices = [
+            Vertex { position: glm::vec3(-0.5, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.5, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(0.5, 0.0, 0.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+        let indices = [0, 1, 2];
+
+        RenderContext::new(
+            CullMode::None,
+            &mut target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |_: &Vertex| [255u8, 255, 255, 255]
+        ).draw_indexed_triangles(&indices, &vertices);
+
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(target.get((x, y)), [0, 0, 0, 0]);
+            }
+        }
+    }
+
+    #[test]
+    fn tessellate_triangle_splits_a_large_triangle_but_leaves_a_small_one_alone() {
+        let v0 = Vertex { position: glm::vec3(0.0, 0.0, 0.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let v1 = Vertex { position: glm::vec3(1.0, 0.0, 0.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+        let v2 = Vertex { position: glm::vec3(0.0, 1.0, 0.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) };
+
+        // Area 5000, well over the threshold: each subdivision level quarters
+        // the area (5000, 1250, 312.5, 78.125), so it takes 3 levels to drop
+        // at or below 100, yielding 4^3 leaf triangles.
+        let p0 = glm::vec4(0.0, 0.0, 0.0, 1.0);
+        let p1 = glm::vec4(100.0, 0.0, 0.0, 1.0);
+        let p2 = glm::vec4(0.0, 100.0, 0.0, 1.0);
+        let mut large_out = Vec::new();
+        RenderContext::<'_, '_, Vertex, fn(&mut Vertex) -> glm::Vec4, fn(&Vertex) -> [u8; 4]>::tessellate_triangle(
+            100.0, 0, p0, p1, p2, v0, v1, v2, &mut large_out);
+        assert_eq!(large_out.len(), 64);
+
+        // Area 8, under the threshold: left as a single triangle.
+        let p0 = glm::vec4(0.0, 0.0, 0.0, 1.0);
+        let p1 = glm::vec4(4.0, 0.0, 0.0, 1.0);
+        let p2 = glm::vec4(0.0, 4.0, 0.0, 1.0);
+        let mut small_out = Vec::new();
+        RenderContext::<'_, '_, Vertex, fn(&mut Vertex) -> glm::Vec4, fn(&Vertex) -> [u8; 4]>::tessellate_triangle(
+            100.0, 0, p0, p1, p2, v0, v1, v2, &mut small_out);
+        assert_eq!(small_out.len(), 1);
+    }
+
+    #[test]
+    fn draw_text_plots_the_embedded_font_glyph_for_a_single_digit() {
+        let mut target = TextureBuffer::new(GLYPH_CELL, 4);
+        let color = [255u8, 255, 255, 255];
+
+        draw_text(&mut target, 0, 0, "1", &color);
+
+        let expected_rows = glyph_rows('1').unwrap();
+        for row in 0..GLYPH_CELL.1 {
+            for col in 0..GLYPH_CELL.0 {
+                let expected_lit = expected_rows[row as usize] & (0x80 >> col) != 0;
+                let pixel = target.get((col, row));
+                if expected_lit {
+                    assert_eq!(pixel, color, "expected ({}, {}) lit", col, row);
+                } else {
+                    assert_eq!(pixel, [0, 0, 0, 0], "expected ({}, {}) unlit", col, row);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,435 +1,702 @@
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use std::marker::PhantomData;
-use std::ops::Add;
-use std::ops::Sub;
-use std::ops::Mul;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Scancode};
 
 extern crate nalgebra_glm as glm;
 
-struct FpsCounter {
-    last_time: std::time::Instant,
-    counter: u32
-}
+use test_rasterization::*;
+
+// A unit cube with per-face normals and UVs, fed to `load_obj` instead of
+// being hardcoded as a `Vertex` array. Each face is listed starting-vertex-
+// first then the remaining three in reverse, which is what makes
+// `parse_obj`'s triangle fan wind the same way the old hand-rolled index
+// buffer did.
+const CUBE_OBJ: &str = "\
+v -1 -1  1
+v  1 -1  1
+v  1  1  1
+v -1  1  1
+v  1  1 -1
+v  1 -1 -1
+v -1 -1 -1
+v -1  1 -1
+
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+
+vn  0  0  1
+vn  1  0  0
+vn  0  0 -1
+vn -1  0  0
+vn  0  1  0
+vn  0 -1  0
+
+f 1/1/1 4/4/1 3/3/1 2/2/1
+f 3/1/2 2/4/2 6/3/2 5/2/2
+f 7/1/3 8/4/3 5/3/3 6/2/3
+f 7/1/4 8/4/4 4/3/4 1/2/4
+f 3/1/5 5/4/5 8/3/5 4/2/5
+f 7/1/6 1/4/6 2/3/6 6/2/6
+";
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let sdl_context = sdl2::init()?;
+    let video_subsystem = sdl_context.video()?;
 
-impl FpsCounter {
-    fn new() -> Self {
-        FpsCounter {
-            last_time: std::time::Instant::now(),
-            counter: 0
-        }
-    }
+    let window = video_subsystem.window("test_rasterization", 1024, 768)
+        .position_centered()
+        .build()?;
+
+    let mut window_size = window.size();
+    let mut canvas = window.into_canvas().build()?;
+
+    // Created once and reused every frame via `texture.update` instead of a
+    // fresh `create_texture_target` call per frame, which otherwise thrashes
+    // an allocation (and a driver-side texture object) 60+ times a second.
+    // `TextureCreator` doesn't borrow `canvas` beyond this call, so it and
+    // the streaming texture it makes can both outlive this statement.
+    let texture_creator = canvas.texture_creator();
+    let sdl_pixel_format = texture_creator.default_pixel_format();
+    let mut present_texture = texture_creator.create_texture_streaming(
+        sdl_pixel_format, window_size.0, window_size.1)?;
+
+    // The texture we copy `texture_buffer` into is created with whatever
+    // pixel format this platform's renderer defaults to, which on some
+    // platforms is BGRA rather than RGBA; rendering into a buffer with the
+    // matching native layout avoids a red/blue channel swap at copy time.
+    let pixel_format = match sdl_pixel_format {
+        sdl2::pixels::PixelFormatEnum::BGRA8888 => PixelFormat::Bgra,
+        _ => PixelFormat::Rgba
+    };
+
+    // Rendered at `SUPERSAMPLE`x the display resolution per axis and box-
+    // downsampled each frame, trading fill rate for smoother silhouette edges.
+    const SUPERSAMPLE: u32 = 2;
+    let render_size = (window_size.0 * SUPERSAMPLE, window_size.1 * SUPERSAMPLE);
+    let mut texture_buffer = TextureBuffer::new_with_format(render_size, 4, pixel_format);
+    let mut depth_buffer = DepthBuffer::new(render_size);
 
-    fn update(&mut self) -> Option<u32> {
-        self.counter += 1;
-        match self.last_time.elapsed().as_millis() {
-            s if s >= 1000 => {
-                let counter = self.counter;
-                self.counter = 0;
-                self.last_time = std::time::Instant::now();
-                Some(counter)
-            },
-            _ => None
-        }
+    let mut angle = 0.0;
+    const ROTATION_SPEED: f32 = 0.6; // radians/second, same visible speed as the old `angle += 0.01` at 60fps
+    let mut clock = Clock::new();
+    const CAMERA_FOVY: f32 = std::f32::consts::PI / 4.0;
+    const CAMERA_NEAR: f32 = 0.1;
+    const CAMERA_FAR: f32 = 100.0;
+    let aspect = window_size.0 as f32 / window_size.1 as f32;
+    let mut camera = Camera::new(aspect, CAMERA_FOVY, CAMERA_NEAR, CAMERA_FAR);
+    let mut ortho_camera = Camera::orthographic(-3.0 * aspect, 3.0 * aspect, -3.0, 3.0, CAMERA_NEAR, CAMERA_FAR);
+    let mut use_orthographic = false;
+    let mut camera_controller = CameraController::new(glm::vec3(0.0, 0.0, 0.0), std::f32::consts::PI, 0.0);
+
+    let mut event_pump = sdl_context.event_pump()?;
+    sdl_context.mouse().set_relative_mouse_mode(true);
+
+    let checkerboard_path = std::env::temp_dir().join("test_rasterization_checkerboard.png");
+    write_checkerboard_png(&checkerboard_path, 256, 32);
+    let mut texture = Texture::load(checkerboard_path.to_str().unwrap())?;
+    texture.generate_mipmaps();
+
+    // Loaded through `load_obj` instead of hardcoded in-line, the way a real
+    // model would be; `CUBE_OBJ` just keeps the asset next to the code that
+    // uses it instead of adding a file the build needs to locate at runtime.
+    let cube_obj_path = std::env::temp_dir().join("test_rasterization_cube.obj");
+    std::fs::write(&cube_obj_path, CUBE_OBJ)?;
+    let (cube_vertices, cube_indices) = load_obj(cube_obj_path.to_str().unwrap())?;
+
+    // A low-poly sphere rendered twice, once per shading path, so the
+    // faceting difference between Gouraud and Phong is visible side by side.
+    let (sphere_vertices, sphere_indices) = generate_sphere_mesh(8, 8);
+    let gouraud_sphere_vertices: Vec<GouraudVertex> = sphere_vertices.iter()
+        .map(|v| GouraudVertex { position: v.position, color: v.normal })
+        .collect();
+
+    // One-shot benchmark comparing the two raster backends against a dense
+    // grid filling the screen. Timed separately from the demo's own cube so
+    // the FPS counter below reflects the lighter scene.
+    {
+        let (bench_vertices, bench_indices) = generate_dense_grid_mesh(158);
+        let mut scanline_target = TextureBuffer::new(window_size, 4);
+        let mut scanline_depth = DepthBuffer::new(window_size);
+        let started = std::time::Instant::now();
+        RenderContext::new(
+            CullMode::None,
+            &mut scanline_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| texture.sample(v.uv)
+        ).with_depth_test(&mut scanline_depth, DepthFunc::Less)
+         .draw_indexed_triangles(&bench_indices, &bench_vertices);
+        println!("Scanline backend: {} triangles in {:.2}ms",
+            bench_indices.len() / 3, started.elapsed().as_secs_f64() * 1000.0);
+
+        let (bench_vertices, bench_indices) = generate_dense_grid_mesh(224);
+        let mut tiled_target = TextureBuffer::new(window_size, 4);
+        let mut tiled_depth = DepthBuffer::new(window_size);
+        let started = std::time::Instant::now();
+        RenderContext::new(
+            CullMode::None,
+            &mut tiled_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| texture.sample(v.uv)
+        ).with_depth_test(&mut tiled_depth, DepthFunc::Less)
+         .with_raster_backend(RasterBackend::Tiled { tile_size: 64 })
+         .draw_indexed_triangles(&bench_indices, &bench_vertices);
+        println!("Tiled backend: {} triangles in {:.2}ms",
+            bench_indices.len() / 3, started.elapsed().as_secs_f64() * 1000.0);
     }
-}
 
-struct TextureBuffer {
-    buffer: Vec<u8>,
-    size: (u32, u32),
-    bytes_per_pixel: u32
-}   
-
-impl TextureBuffer {
-    fn new(size: (u32, u32), bytes_per_pixel: u32) -> Self {
-        TextureBuffer {
-            buffer: vec![0; (size.0 * size.1 * bytes_per_pixel) as usize],
-            size: size,
-            bytes_per_pixel: bytes_per_pixel
+    // One-shot benchmark for `Renderer`: many small draw calls (as a frame
+    // issuing lots of individually-culled objects would) either spin up a
+    // fresh rayon thread pool per call, or reuse one held across all of them.
+    {
+        let (bench_vertices, bench_indices) = generate_dense_grid_mesh(24);
+        const DRAW_CALLS: u32 = 200;
+
+        let mut fresh_pool_target = TextureBuffer::new(window_size, 4);
+        let started = std::time::Instant::now();
+        for _ in 0..DRAW_CALLS {
+            let fresh_pool = Renderer::new(4);
+            fresh_pool.install(|| {
+                RenderContext::new(
+                    CullMode::None,
+                    &mut fresh_pool_target,
+                    |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                    |v: &Vertex| texture.sample(v.uv)
+                ).draw_indexed_triangles(&bench_indices, &bench_vertices);
+            });
         }
-    }
-
-    fn pitch(&self) -> usize {
-        (self.size.0 * self.bytes_per_pixel) as usize
-    }
-
-    fn set(&mut self, point: (u32, u32), color: &[u8; 4]) {
-        let index = (self.bytes_per_pixel * (point.1 * self.size.0 + point.0)) as usize;
-        unsafe {
-            std::ptr::copy_nonoverlapping(color.as_ptr(),
-                self.buffer.as_mut_ptr().offset(index as isize),
-                std::mem::size_of_val(color));
+        println!("Renderer, fresh pool per call: {} draw calls in {:.2}ms",
+            DRAW_CALLS, started.elapsed().as_secs_f64() * 1000.0);
+
+        let mut reused_pool_target = TextureBuffer::new(window_size, 4);
+        let reused_pool = Renderer::new(4);
+        let started = std::time::Instant::now();
+        for _ in 0..DRAW_CALLS {
+            reused_pool.install(|| {
+                RenderContext::new(
+                    CullMode::None,
+                    &mut reused_pool_target,
+                    |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+                    |v: &Vertex| texture.sample(v.uv)
+                ).draw_indexed_triangles(&bench_indices, &bench_vertices);
+            });
         }
+        println!("Renderer, reused pool: {} draw calls in {:.2}ms",
+            DRAW_CALLS, started.elapsed().as_secs_f64() * 1000.0);
     }
 
-    fn clear(&mut self, value: u8) {
-        for v in &mut self.buffer {
-            *v = value;
+    // One-shot benchmark for the depth-only pre-pass: four full-screen quads
+    // stacked along z, shaded with a deliberately expensive pixel shader, so
+    // the naive single pass shades every overdrawn fragment while the
+    // pre-pass only shades the one quad that actually survives depth testing
+    // at each pixel.
+    {
+        fn slow_shade(v: &Vertex) -> [u8; 4] {
+            let mut acc = 0.0f32;
+            for i in 0..400 {
+                acc += (v.uv.x * i as f32 + v.uv.y).sin();
+            }
+            let c = ((acc.sin() * 0.5 + 0.5) * 255.0) as u8;
+            [c, c, c, 255]
         }
-    }
-}
 
-struct Camera {
-    view: glm::Mat4,
-    projection: glm::Mat4
-}
-
-impl Camera {
-    fn new(aspect: f32, fovy: f32, near: f32, far: f32) -> Self {
-        Camera {
-            view: glm::identity(),
-            projection: glm::perspective(aspect, fovy, near, far)
-        }
+        let quad_vertices = [
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.8), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, 0.8), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0,  1.0, 0.8), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, 0.8), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.6), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, 0.6), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0,  1.0, 0.6), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, 0.6), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.4), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, 0.4), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0,  1.0, 0.4), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, 0.4), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+
+            Vertex { position: glm::vec3(-1.0, -1.0, 0.2), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0, -1.0, 0.2), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3( 1.0,  1.0, 0.2), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) },
+            Vertex { position: glm::vec3(-1.0,  1.0, 0.2), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, 1.0) }
+        ];
+
+        let quad_indices = [
+            0,  2,  1,  0,  3,  2,
+            4,  6,  5,  4,  7,  6,
+            8,  10, 9,  8,  11, 10,
+            12, 14, 13, 12, 15, 14
+        ];
+
+        let mut single_pass_target = TextureBuffer::new(window_size, 4);
+        let mut single_pass_depth = DepthBuffer::new(window_size);
+        let started = std::time::Instant::now();
+        RenderContext::new(
+            CullMode::None,
+            &mut single_pass_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            slow_shade
+        ).with_depth_test(&mut single_pass_depth, DepthFunc::Less)
+         .draw_indexed_triangles(&quad_indices, &quad_vertices);
+        let single_pass_elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        println!("Overdraw, single pass: {:.2}ms", single_pass_elapsed);
+
+        let mut prepass_target = TextureBuffer::new(window_size, 4);
+        let mut prepass_depth = DepthBuffer::new(window_size);
+        let started = std::time::Instant::now();
+        RenderContext::new(
+            CullMode::None,
+            &mut prepass_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            slow_shade
+        ).with_depth_test(&mut prepass_depth, DepthFunc::Less)
+         .with_color_write(false)
+         .draw_indexed_triangles(&quad_indices, &quad_vertices);
+        RenderContext::new(
+            CullMode::None,
+            &mut prepass_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            slow_shade
+        ).with_depth_test(&mut prepass_depth, DepthFunc::Equal)
+         .draw_indexed_triangles(&quad_indices, &quad_vertices);
+        let prepass_elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        println!("Overdraw, depth pre-pass: {:.2}ms ({:.1}x over single pass)",
+            prepass_elapsed, single_pass_elapsed / prepass_elapsed);
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-struct Vertex {
-    position: glm::Vec3,
-    uv: glm::Vec2
-}
-
-impl Add<Vertex> for Vertex {
-    type Output = Vertex;
-    fn add(self, rhs: Vertex) -> Self::Output {
-        Vertex {
-            position: self.position + rhs.position,
-            uv: self.uv + rhs.uv
-        }
+    // One-shot benchmark for instanced rendering: a 10x10 grid of cubes
+    // drawn with a single `draw_indexed_instanced` call instead of 100
+    // separate `draw_indexed_triangles` calls, each of which would have
+    // re-cloned the base cube vertices.
+    {
+        let mut grid_target = TextureBuffer::new(window_size, 4);
+        let mut grid_depth = DepthBuffer::new(window_size);
+        let view_projection = camera.view_projection();
+        let grid_size = 10;
+        let spacing = 3.0;
+        let started = std::time::Instant::now();
+        RenderContext::new(
+            CullMode::Back,
+            &mut grid_target,
+            |v: &mut Vertex| glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| texture.sample(v.uv)
+        ).with_depth_test(&mut grid_depth, DepthFunc::Less)
+         .draw_indexed_instanced(&cube_indices, &cube_vertices, grid_size * grid_size, |i| {
+            let (row, col) = (i / grid_size, i % grid_size);
+            let offset = glm::vec3(
+                (col as f32 - (grid_size - 1) as f32 / 2.0) * spacing,
+                0.0,
+                (row as f32 - (grid_size - 1) as f32 / 2.0) * spacing + 20.0
+            );
+            view_projection * glm::translation(&offset)
+        });
+        println!("Instanced grid: {} cubes ({} triangles) in {:.2}ms",
+            grid_size * grid_size, grid_size * grid_size * cube_indices.len() / 3,
+            started.elapsed().as_secs_f64() * 1000.0);
     }
-}
 
-impl Sub<Vertex> for Vertex {
-    type Output = Vertex;
-    fn sub(self, rhs: Vertex) -> Self::Output {
-        Vertex {
-            position: self.position - rhs.position,
-            uv: self.uv - rhs.uv
-        }
+    // One-shot demo of multi-texturing via `Sampler`: a cube blending a
+    // brick-like checkerboard with a solid moss color by a UV.y gradient,
+    // instead of sampling a single material.
+    {
+        let brick = Texture::checkerboard(64, 64, 8, [180u8, 90, 60, 255], [140u8, 70, 45, 255]);
+        let moss = Texture::solid(64, 64, [60u8, 110, 50, 255]);
+        let sampler = Sampler::new(vec![&brick, &moss]);
+
+        let mut blend_target = TextureBuffer::new(window_size, 4);
+        let mut blend_depth = DepthBuffer::new(window_size);
+        let blend_mvp = camera.projection * camera.view * glm::translation(&glm::vec3(0.0, 0.0, 5.0));
+        RenderContext::new(
+            CullMode::Back,
+            &mut blend_target,
+            |v: &mut Vertex| blend_mvp * glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| blend_colors(sampler.sample(0, v.uv), sampler.sample(1, v.uv), v.uv.y)
+        ).with_depth_test(&mut blend_depth, DepthFunc::Less)
+         .draw_indexed_triangles(&cube_indices, &cube_vertices);
+        println!("Multi-textured cube rendered via Sampler blend");
     }
-}
-
-impl Mul<f32> for Vertex {
-    type Output = Vertex;
-    fn mul(self, rhs: f32) -> Self::Output {
-        Vertex {
-            position: self.position * rhs,
-            uv: self.uv * rhs
-        }
-    } 
-}
-
-trait Linear: Copy + Add<Self, Output=Self> + Sub<Self, Output=Self> + Mul<f32, Output=Self> {}
-
-impl Linear for Vertex {}
 
-struct RenderContext<'a, V: Clone + Linear, 
-    VS: Fn(&mut V) -> glm::Vec4, 
-    PS: Fn(&V) -> [u8; 4]> {   
-    cull_backface: bool,     
-    target: &'a mut TextureBuffer,
-    vertex_shader: VS,
-    pixel_shader: PS,
-    phantom: PhantomData<V>
-}
-
-impl<'a, V: Clone + Linear, 
-    VS: Fn(&mut V) -> glm::Vec4, 
-    PS: Fn(&V) -> [u8; 4]> RenderContext<'a, V, VS, PS> {
-    fn new(cull_backface: bool, target: &'a mut TextureBuffer, vertex_shader: VS, pixel_shader: PS) -> Self {
-        RenderContext {
-            cull_backface,
-            target,
-            vertex_shader,
-            pixel_shader,
-            phantom: PhantomData
-        }
-    }
-
-    fn draw_indexed_triangles(&mut self, indices: &[usize], vertices: &[V]) {
-        let mut vertices = vertices.to_vec();
-        let positions = vertices.
-            iter_mut().
-            map(&self.vertex_shader).
-            collect::<Vec<_>>();
-        let mut current_indices = indices;
-        loop {
-            if let [i0, i1, i2, ref rest @ ..] = *current_indices {
-                current_indices = rest;
-                let mut p0 = positions[i0];
-                let mut p1 = positions[i1];
-                let mut p2 = positions[i2];
-                let v0 = &vertices[i0];
-                let v1 = &vertices[i1];
-                let v2 = &vertices[i2];
-                p0 /= p0.w;
-                p1 /= p1.w;
-                p2 /= p2.w;
-                if self.cull_backface {
-                    let d0 = p2 - p0;
-                    let d1 = p2 - p1;
-                    if (d0.x * d1.y) - (d0.y * d1.x) < 0.0 {
-                        continue;
-                    }
-                }
-                self.draw_triangle(
-                    &self.transform_to_target_coordinates(&p0), 
-                    &self.transform_to_target_coordinates(&p1), 
-                    &self.transform_to_target_coordinates(&p2), 
-                    v0, v1, v2
-                );
-            } else {
-                break;
-            }
-        }
-    }
-    
-    fn draw_triangle(&mut self, 
-        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
-        v0: &V, v1: &V, v2: &V) {
-        let mut p0 = p0;
-        let mut p1 = p1;
-        let mut p2 = p2;
-        let mut v0 = v0;
-        let mut v1 = v1;
-        let mut v2 = v2;
-
-        if p1.y < p0.y {
-            std::mem::swap(&mut p0, &mut p1);
-            std::mem::swap(&mut v0, &mut v1);
-        }
-        if p2.y < p1.y {
-            std::mem::swap(&mut p1, &mut p2);
-            std::mem::swap(&mut v1, &mut v2);
-        }
-        if p1.y < p0.y {
-            std::mem::swap(&mut p0, &mut p1);
-            std::mem::swap(&mut v0, &mut v1);
-        }
-
-        //natural flat top
-        if p0.y == p1.y { 
-            if p1.x < p0.x {
-                std::mem::swap(&mut p0, &mut p1);
-                std::mem::swap(&mut v0, &mut v1);
-            }
-            self.draw_flat_top_triangle(p0, p1, p2, v0, v1, v2);
-        }
-        //natural flat bottom
-        else if p1.y == p2.y {
-            if p2.x < p1.x {
-                std::mem::swap(&mut p1, &mut p2);
-                std::mem::swap(&mut v1, &mut v2);
-            }
-            self.draw_flat_bottom_triangle(p0, p1, p2, v0, v1, v2);
-        }
-        //general triangle
-        else {
-            let alpha = (p1.y - p0.y) / (p2.y - p0.y);
-            let pi = p0 + (p2 - p0) * alpha;
-            let vi = *v0 + (*v2 - *v0) * alpha;
-            //major right
-            if p1.x < pi.x {
-                self.draw_flat_bottom_triangle(p0, p1, &pi, v0, v1, &vi);
-                self.draw_flat_top_triangle(p1, &pi, p2, v1, &vi, v2);
-            }
-            //major left
-            else {
-                self.draw_flat_bottom_triangle(p0, &pi, p1, v0, &vi, v1);
-                self.draw_flat_top_triangle(&pi, p1, p2, &vi, v1, v2);
+    // One-shot demo of a colored + textured material: a checkerboard quad
+    // tinted per-vertex, exercising two interpolated attributes (`uv` and
+    // `color`) composing through the same `Linear` pipeline at once.
+    {
+        let tile = Texture::checkerboard(64, 64, 8, [220u8, 220, 220, 255], [80u8, 80, 80, 255]);
+        let tinted_vertices = [
+            ColoredTexturedVertex { position: glm::vec3(-1.0, -1.0, 5.0), uv: glm::vec2(0.0, 0.0), color: glm::vec3(1.0, 0.3, 0.3) },
+            ColoredTexturedVertex { position: glm::vec3(1.0, -1.0, 5.0), uv: glm::vec2(1.0, 0.0), color: glm::vec3(0.3, 1.0, 0.3) },
+            ColoredTexturedVertex { position: glm::vec3(1.0, 1.0, 5.0), uv: glm::vec2(1.0, 1.0), color: glm::vec3(0.3, 0.3, 1.0) },
+            ColoredTexturedVertex { position: glm::vec3(-1.0, 1.0, 5.0), uv: glm::vec2(0.0, 1.0), color: glm::vec3(1.0, 1.0, 0.3) }
+        ];
+        let tinted_indices = [0, 1, 2, 0, 2, 3];
+
+        let mut tinted_target = TextureBuffer::new(window_size, 4);
+        let mut tinted_depth = DepthBuffer::new(window_size);
+        let tinted_mvp = camera.projection * camera.view;
+        RenderContext::new(
+            CullMode::None,
+            &mut tinted_target,
+            |v: &mut ColoredTexturedVertex| tinted_mvp * glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &ColoredTexturedVertex| {
+                let texel = tile.sample(v.uv);
+                [
+                    (texel[0] as f32 * v.color.x).round() as u8,
+                    (texel[1] as f32 * v.color.y).round() as u8,
+                    (texel[2] as f32 * v.color.z).round() as u8,
+                    texel[3]
+                ]
             }
-        }
+        ).with_depth_test(&mut tinted_depth, DepthFunc::Less)
+         .draw_indexed_triangles(&tinted_indices, &tinted_vertices);
+        println!("Tinted, textured quad rendered via ColoredTexturedVertex");
     }
 
-    fn draw_flat_top_triangle(&mut self, 
-        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
-        v0: &V, v1: &V, v2: &V) {
-
-        let slope1 = (p2.x - p0.x) / (p2.y - p0.y);
-        let slope2 = (p2.x - p1.x) / (p2.y - p1.y);
-
-        self.draw_flat_triangle_common(p0, p1, p2, [(slope1, p0), (slope2, p1)], v0, v1, v2);
-    }
-
-    fn draw_flat_bottom_triangle(&mut self, 
-        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4,
-        v0: &V, v1: &V, v2: &V) {
-
-        let slope1 = (p1.x - p0.x) / (p1.y - p0.y);
-        let slope2 = (p2.x - p0.x) / (p2.y - p0.y);
-
-        self.draw_flat_triangle_common(p0, p1, p2, [(slope1, p0), (slope2, p0)], v0, v1, v2);
-    }
-
-    fn draw_flat_triangle_common(&mut self, 
-        p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4, lines: [(f32, &glm::Vec4); 2],
-        v0: &V, v1: &V, v2: &V) {
-    
-        let [(slope0, line_start0), 
-            (slope1, line_start1)] = lines;
-            
-        let snap = |c: f32| {
-            (c - 0.5).ceil()
-        };
-
-        let y_start = snap(p0.y).max(0.0) as i32;
-        let y_end = snap(p2.y).min(self.target.size.1 as f32) as i32;
-            
-        for y in y_start..y_end {
-            let px0 = slope0 * (y as f32 + 0.5 - line_start0.y) + line_start0.x;
-            let px1 = slope1 * (y as f32 + 0.5 - line_start1.y) + line_start1.x;
-
-            let x_start = snap(px0).max(0.0) as i32;
-            let x_end = snap(px1).min(self.target.size.0 as f32) as i32;
-
-            for x in x_start..x_end {
-                let f = Self::barycentric_coordinates(
-                    &glm::vec4(x as f32, y as f32, 0.0, 0.0), &p0, &p1, &p2
+    // One-shot demo of tangent-space normal mapping: per-face tangents are
+    // computed once from the cube's existing positions/uvs/normals, then the
+    // pixel shader builds a TBN basis per fragment and perturbs the
+    // interpolated normal with a sampled (synthetic, procedurally generated)
+    // normal map before lighting.
+    {
+        let tangents = compute_tangents(&cube_vertices, &cube_indices);
+        let tangent_vertices: Vec<TangentVertex> = cube_vertices.iter().zip(&tangents)
+            .map(|(v, &tangent)| TangentVertex { position: v.position, uv: v.uv, normal: v.normal, tangent })
+            .collect();
+        // A flat normal map (pointing straight out of the surface, encoded
+        // as RGB = 0.5 + 0.5*(0,0,1)) with a few bumpier, darker-blue cells
+        // so the perturbation is visible.
+        let normal_map = Texture::checkerboard(64, 64, 16, [128u8, 128, 255, 255], [160u8, 160, 220, 255]);
+
+        let mut normal_map_target = TextureBuffer::new(window_size, 4);
+        let mut normal_map_depth = DepthBuffer::new(window_size);
+        let normal_map_mvp = camera.projection * camera.view * glm::translation(&glm::vec3(0.0, 0.0, 5.0));
+        let light_dir = glm::normalize(&glm::vec3(0.4, 0.8, -0.5));
+        RenderContext::new(
+            CullMode::Back,
+            &mut normal_map_target,
+            |v: &mut TangentVertex| normal_map_mvp * glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &TangentVertex| {
+                let normal = glm::normalize(&v.normal);
+                let tangent = glm::normalize(&v.tangent);
+                let bitangent = glm::cross(&normal, &tangent);
+
+                let sample = normal_map.sample(v.uv);
+                let map_normal = glm::vec3(
+                    sample[0] as f32 / 255.0 * 2.0 - 1.0,
+                    sample[1] as f32 / 255.0 * 2.0 - 1.0,
+                    sample[2] as f32 / 255.0 * 2.0 - 1.0
                 );
-                let interpolated = *v0 * f.0 + *v1 * f.1 + *v2 * f.2;
-                let color = (self.pixel_shader)(&interpolated);
-                self.target.set((x as u32, y as u32), &color);
-            }
-        }
-    }
+                let perturbed = glm::normalize(&(tangent * map_normal.x + bitangent * map_normal.y + normal * map_normal.z));
 
-    fn barycentric_coordinates(p: &glm::Vec4, p0: &glm::Vec4, p1: &glm::Vec4, p2: &glm::Vec4) -> (f32, f32, f32) {
-        let v0 = p1 - p0;
-        let v1 = p2 - p0; 
-        let v2 = p - p0;
-        let d00 = glm::dot(&v0.xy(), &v0.xy());
-        let d01 = glm::dot(&v0.xy(), &v1.xy());
-        let d11 = glm::dot(&v1.xy(), &v1.xy());
-        let d20 = glm::dot(&v2.xy(), &v0.xy());
-        let d21 = glm::dot(&v2.xy(), &v1.xy());
-        let denom = d00 * d11 - d01 * d01;
-        let f1 = (d11 * d20 - d01 * d21) / denom;
-        let f2 = (d00 * d21 - d01 * d20) / denom;
-        let f0 = 1.0 - f1 - f2;
-        (f0, f1, f2)
+                let diffuse = f32::max(glm::dot(&perturbed, &light_dir), 0.0);
+                [(220.0 * diffuse) as u8, (220.0 * diffuse) as u8, (220.0 * diffuse) as u8, 255]
+            }
+        ).with_depth_test(&mut normal_map_depth, DepthFunc::Less)
+         .draw_indexed_triangles(&cube_indices, &tangent_vertices);
+        println!("Normal-mapped cube rendered via compute_tangents + TBN basis");
     }
 
-    fn transform_to_target_coordinates(&self, v: &glm::Vec4) -> glm::Vec4 {
-        glm::vec4(
-            (v.x + 1.0) * (self.target.size.0 as f32 / 2.0),
-            (v.y + 1.0) * (self.target.size.1 as f32 / 2.0),
-            v.z,
-            v.w
-        )
+    // One-shot demo of `OrbitCamera`: a model-viewer-style alternative to
+    // `CameraController`'s free-fly, driven the same way SDL would drive it
+    // (drag to orbit, scroll to zoom) but with a single simulated drag/zoom
+    // instead of a full interactive loop.
+    {
+        let mut orbit_camera = OrbitCamera::new(glm::vec3(0.0, 0.0, 0.0), 8.0, 0.0, 0.0);
+        orbit_camera.process_mouse(300.0, -100.0, 0.005);
+        orbit_camera.process_scroll(1.0, 0.5, 2.0);
+
+        let mut orbit_target = TextureBuffer::new(window_size, 4);
+        let mut orbit_depth = DepthBuffer::new(window_size);
+        let orbit_mvp = camera.projection * orbit_camera.view_matrix();
+        RenderContext::new(
+            CullMode::Back,
+            &mut orbit_target,
+            |v: &mut Vertex| orbit_mvp * glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            |v: &Vertex| texture.sample(v.uv)
+        ).with_depth_test(&mut orbit_depth, DepthFunc::Less)
+         .draw_indexed_triangles(&cube_indices, &cube_vertices);
+        println!("Cube rendered via OrbitCamera at eye {:?}", orbit_camera.eye());
     }
 
-}
-
-pub fn main() {
-    let sdl_context = sdl2::init().unwrap();
-    let video_subsystem = sdl_context.video().unwrap();
- 
-    let window = video_subsystem.window("test_rasterization", 1024, 768)
-        .position_centered()
-        .build()
-        .unwrap();
+    let mut fps_counter = FpsCounter::new();
+    // Last one-second fps sample from `fps_counter.update()`, redrawn into
+    // the HUD every frame since `update()` itself only refreshes once a
+    // second.
+    let mut last_fps: u32 = 0;
 
-    let window_size = window.size();
-    let mut texture_buffer = TextureBuffer::new(window_size, 4);
-    
-    let mut angle = 0.0;
-    let camera = Camera::new(
-        window_size.0 as f32 / window_size.1 as f32,
-        std::f32::consts::PI / 4.0,
-        0.1,
-        100.0
-    );
- 
-    let mut canvas = window.into_canvas().build().unwrap();
-    let mut event_pump = sdl_context.event_pump().unwrap();
-
-    let cube_vertices = [
-        Vertex { position: glm::vec3(-1.0, -1.0, 1.0), uv: glm::vec2(0.0, 0.0) },
-        Vertex { position: glm::vec3( 1.0, -1.0, 1.0), uv: glm::vec2(1.0, 0.0) },
-        Vertex { position: glm::vec3( 1.0,  1.0, 1.0), uv: glm::vec2(1.0, 1.0) },
-        Vertex { position: glm::vec3(-1.0,  1.0, 1.0), uv: glm::vec2(0.0, 1.0) },
-        
-        Vertex { position: glm::vec3(1.0,  1.0,  1.0), uv: glm::vec2(0.0, 0.0) },
-        Vertex { position: glm::vec3(1.0,  1.0, -1.0), uv: glm::vec2(1.0, 0.0) },
-        Vertex { position: glm::vec3(1.0, -1.0, -1.0), uv: glm::vec2(1.0, 1.0) },
-        Vertex { position: glm::vec3(1.0, -1.0,  1.0), uv: glm::vec2(0.0, 1.0) },
-    
-        Vertex { position: glm::vec3(-1.0, -1.0, -1.0), uv: glm::vec2(0.0, 0.0) },
-        Vertex { position: glm::vec3( 1.0, -1.0, -1.0), uv: glm::vec2(1.0, 0.0) },
-        Vertex { position: glm::vec3( 1.0,  1.0, -1.0), uv: glm::vec2(1.0, 1.0) },
-        Vertex { position: glm::vec3(-1.0,  1.0, -1.0), uv: glm::vec2(0.0, 1.0) },
-    
-        Vertex { position: glm::vec3(-1.0, -1.0, -1.0), uv: glm::vec2(0.0, 0.0) },
-        Vertex { position: glm::vec3(-1.0, -1.0,  1.0), uv: glm::vec2(1.0, 0.0) },
-        Vertex { position: glm::vec3(-1.0,  1.0,  1.0), uv: glm::vec2(1.0, 1.0) },
-        Vertex { position: glm::vec3(-1.0,  1.0, -1.0), uv: glm::vec2(0.0, 1.0) },
-    
-        Vertex { position: glm::vec3( 1.0, 1.0,  1.0), uv: glm::vec2(0.0, 0.0) },
-        Vertex { position: glm::vec3(-1.0, 1.0,  1.0), uv: glm::vec2(1.0, 0.0) },
-        Vertex { position: glm::vec3(-1.0, 1.0, -1.0), uv: glm::vec2(1.0, 1.0) },
-        Vertex { position: glm::vec3( 1.0, 1.0, -1.0), uv: glm::vec2(0.0, 1.0) },
-        
-        Vertex { position: glm::vec3(-1.0, -1.0, -1.0), uv: glm::vec2(0.0, 0.0) },
-        Vertex { position: glm::vec3( 1.0, -1.0, -1.0), uv: glm::vec2(1.0, 0.0) },
-        Vertex { position: glm::vec3( 1.0, -1.0,  1.0), uv: glm::vec2(1.0, 1.0) },
-        Vertex { position: glm::vec3(-1.0, -1.0,  1.0), uv: glm::vec2(0.0, 1.0) }
-    ];
-
-    let cube_indices = [
-        0,  2,  1,  0,  3,  2,
-        4,  5,  6,  4,  6,  7,
-        8,  9,  10, 8,  10, 11, 
-        12, 14, 13, 12, 15, 14, 
-        16, 17, 18, 16, 18, 19, 
-        20, 22, 21, 20, 23, 22 
-    ];
+    // While `paused`, `angle` no longer advances on its own; `step_requested`
+    // lets the right arrow key advance it by exactly one frame's worth at a
+    // time, for inspecting a specific frame (e.g. combined with F2's PNG save).
+    let mut paused = false;
+    let mut step_requested = false;
 
-    let mut fps_counter = FpsCounter::new();
+    // `Some` while F3-toggled recording is active; dropping it (on the next
+    // F3 press) finalizes the GIF file.
+    let mut gif_recorder: Option<GifRecorder> = None;
 
     'running: loop {
         for event in event_pump.poll_iter() {
-            match event {   
+            match event {
                 Event::Quit {..} |
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
+                Event::KeyDown { keycode: Some(Keycode::P), .. } => {
+                    use_orthographic = !use_orthographic;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Space), repeat: false, .. } => {
+                    paused = !paused;
+                },
+                Event::KeyDown { keycode: Some(Keycode::Right), repeat: false, .. } if paused => {
+                    step_requested = true;
+                },
+                Event::KeyDown { keycode: Some(Keycode::F2), .. } => {
+                    // Saved side by side so the supersampling resolve's effect on
+                    // jagged edges can be compared directly against the raw render.
+                    if let Err(e) = texture_buffer.save_png("screenshot_supersampled.png") {
+                        println!("Failed to save screenshot: {}", e);
+                    }
+                    if let Err(e) = texture_buffer.resolve(SUPERSAMPLE).save_png("screenshot.png") {
+                        println!("Failed to save screenshot: {}", e);
+                    }
+                },
+                Event::KeyDown { keycode: Some(Keycode::F3), repeat: false, .. } => {
+                    // Dropping the previous recorder (if any) finalizes its file.
+                    gif_recorder = match gif_recorder.take() {
+                        Some(_) => {
+                            println!("Stopped recording capture.gif");
+                            None
+                        },
+                        None => match GifRecorder::new("capture.gif", window_size, 30.0) {
+                            Ok(recorder) => {
+                                println!("Recording to capture.gif");
+                                Some(recorder)
+                            },
+                            Err(e) => {
+                                println!("Failed to start GIF recording: {}", e);
+                                None
+                            }
+                        }
+                    };
+                },
+                Event::MouseMotion { xrel, yrel, .. } => {
+                    camera_controller.process_mouse(xrel as f32, yrel as f32, 0.002);
+                },
+                Event::Window { win_event: WindowEvent::SizeChanged(new_width, new_height), .. } => {
+                    window_size = (new_width as u32, new_height as u32);
+                    let render_size = (window_size.0 * SUPERSAMPLE, window_size.1 * SUPERSAMPLE);
+                    texture_buffer.resize(render_size);
+                    depth_buffer = DepthBuffer::new(render_size);
+                    present_texture = texture_creator.create_texture_streaming(
+                        sdl_pixel_format, window_size.0, window_size.1)?;
+
+                    let aspect = window_size.0 as f32 / window_size.1 as f32;
+                    camera.set_aspect(aspect);
+                    ortho_camera.projection = glm::ortho(-3.0 * aspect, 3.0 * aspect, -3.0, 3.0, CAMERA_NEAR, CAMERA_FAR);
+
+                    // The recorder was created for the old window size; a
+                    // GIF can't change canvas size mid-stream, so finalize
+                    // it rather than let the next `capture` panic.
+                    if gif_recorder.take().is_some() {
+                        println!("Stopped recording capture.gif (window resized)");
+                    }
+                },
                 _ => {}
             }
         }
 
+        let keyboard_state = event_pump.keyboard_state();
+        camera_controller.process_keyboard(
+            keyboard_state.is_scancode_pressed(Scancode::W),
+            keyboard_state.is_scancode_pressed(Scancode::S),
+            keyboard_state.is_scancode_pressed(Scancode::A),
+            keyboard_state.is_scancode_pressed(Scancode::D),
+            0.05
+        );
+        camera.look_at(
+            camera_controller.position,
+            camera_controller.position + camera_controller.forward(),
+            glm::vec3(0.0, 1.0, 0.0)
+        );
+
         texture_buffer.clear(0);
+        depth_buffer.clear_depth(f32::INFINITY);
+
+        let projection = if use_orthographic { ortho_camera.projection } else { camera.projection };
 
-        angle += 0.01;
-        let model = glm::translation(&glm::vec3(0.0, 0.0, 5.0)) * 
+        let dt = clock.tick();
+        if !paused || step_requested {
+            angle += ROTATION_SPEED * dt;
+            step_requested = false;
+        }
+        let model = glm::translation(&glm::vec3(0.0, 0.0, 5.0)) *
             glm::rotation(angle, &glm::vec3(0.0, 1.0, 0.0));
-        let mvp = camera.projection * camera.view * model;
+        let mvp = projection * camera.view * model;
+        let normal_matrix = glm::mat4_to_mat3(&model);
+        let light_dir = glm::normalize(&glm::vec3(0.4, 0.8, -0.5));
         let mut render_context = RenderContext::new(
-            true,
-            &mut texture_buffer, 
+            CullMode::Back,
+            &mut texture_buffer,
             |v: &mut Vertex| {
                 let p = v.position;
+                v.normal = normal_matrix * v.normal;
                 mvp * glm::vec4(p.x, p.y, p.z, 1.0)
             },
             |v: &Vertex| {
-                [0, (v.uv.y * 255.0) as u8, (v.uv.x * 255.0) as u8, 255]
+                let normal = glm::normalize(&v.normal);
+                let diffuse = f32::max(glm::dot(&normal, &light_dir), 0.0);
+                let base = texture.sample(v.uv);
+                [
+                    (base[0] as f32 * diffuse) as u8,
+                    (base[1] as f32 * diffuse) as u8,
+                    (base[2] as f32 * diffuse) as u8,
+                    base[3]
+                ]
             }
-        );
+        ).with_depth_test(&mut depth_buffer, DepthFunc::Less);
         render_context.draw_indexed_triangles(&cube_indices, &cube_vertices);
 
-        let texture_creator = canvas.texture_creator();
-        let mut texture = texture_creator
-            .create_texture_target(texture_creator.default_pixel_format(),
-                 window_size.0,
-                 window_size.1)
-            .unwrap();
-        texture.update(None, &texture_buffer.buffer, 
-            texture_buffer.pitch()).unwrap();
+        // A grid under the cube for spatial orientation.
+        let grid_mvp = projection * camera.view * glm::translation(&glm::vec3(0.0, -1.5, 5.0));
+        draw_grid(
+            &mut texture_buffer, Some(&mut depth_buffer),
+            |v: &mut Vertex| grid_mvp * glm::vec4(v.position.x, v.position.y, v.position.z, 1.0),
+            1.0, 10.0, [100u8, 100, 100, 255]
+        );
 
-        canvas.copy(&texture, None, None).unwrap();
-        canvas.present();
+        // A translucent pane of "glass" floating in front of the cube.
+        let glass_vertices = [
+            Vertex { position: glm::vec3(-1.5, -1.5, 3.0), uv: glm::vec2(0.0, 0.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+            Vertex { position: glm::vec3( 1.5, -1.5, 3.0), uv: glm::vec2(1.0, 0.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+            Vertex { position: glm::vec3( 1.5,  1.5, 3.0), uv: glm::vec2(1.0, 1.0), normal: glm::vec3(0.0, 0.0, -1.0) },
+            Vertex { position: glm::vec3(-1.5,  1.5, 3.0), uv: glm::vec2(0.0, 1.0), normal: glm::vec3(0.0, 0.0, -1.0) }
+        ];
+        let glass_indices = [0, 1, 2, 0, 2, 3];
+        let glass_mvp = projection * camera.view;
+        let mut glass_context = RenderContext::new(
+            CullMode::Back,
+            &mut texture_buffer,
+            |v: &mut Vertex| {
+                let p = v.position;
+                glass_mvp * glm::vec4(p.x, p.y, p.z, 1.0)
+            },
+            |_: &Vertex| [120u8, 200, 255, 96]
+        ).with_depth_test(&mut depth_buffer, DepthFunc::Less)
+         .with_blend_mode(BlendMode::AlphaBlend)
+         .with_sort_transparent(true);
+        glass_context.draw_indexed_triangles(&glass_indices, &glass_vertices);
+
+        // Gouraud sphere (left): lighting is resolved once per vertex and the
+        // resulting color is interpolated, so the low-poly facets band visibly.
+        let gouraud_model = glm::translation(&glm::vec3(-2.5, 0.0, 6.0)) *
+            glm::rotation(angle, &glm::vec3(0.0, 1.0, 0.0));
+        let gouraud_mvp = projection * camera.view * gouraud_model;
+        let gouraud_normal_matrix = glm::mat4_to_mat3(&gouraud_model);
+        let mut gouraud_context = RenderContext::new(
+            CullMode::Back,
+            &mut texture_buffer,
+            |v: &mut GouraudVertex| {
+                let p = v.position;
+                let normal = glm::normalize(&(gouraud_normal_matrix * v.color));
+                let diffuse = f32::max(glm::dot(&normal, &light_dir), 0.0);
+                v.color = glm::vec3(1.0, 0.4, 0.2) * diffuse;
+                gouraud_mvp * glm::vec4(p.x, p.y, p.z, 1.0)
+            },
+            |v: &GouraudVertex| [
+                (v.color.x.clamp(0.0, 1.0) * 255.0) as u8,
+                (v.color.y.clamp(0.0, 1.0) * 255.0) as u8,
+                (v.color.z.clamp(0.0, 1.0) * 255.0) as u8,
+                255
+            ]
+        ).with_depth_test(&mut depth_buffer, DepthFunc::Less);
+        gouraud_context.draw_indexed_triangles(&sphere_indices, &gouraud_sphere_vertices);
+
+        // Phong sphere (right): the normal itself is interpolated and lit per
+        // fragment, so the same low-poly mesh shades smoothly instead.
+        let phong_model = glm::translation(&glm::vec3(2.5, 0.0, 6.0)) *
+            glm::rotation(angle, &glm::vec3(0.0, 1.0, 0.0));
+        let phong_mvp = projection * camera.view * phong_model;
+        let phong_normal_matrix = glm::mat4_to_mat3(&phong_model);
+        let mut phong_context = RenderContext::new(
+            CullMode::Back,
+            &mut texture_buffer,
+            |v: &mut Vertex| {
+                let p = v.position;
+                v.normal = phong_normal_matrix * v.normal;
+                phong_mvp * glm::vec4(p.x, p.y, p.z, 1.0)
+            },
+            |v: &Vertex| {
+                let normal = glm::normalize(&v.normal);
+                let diffuse = f32::max(glm::dot(&normal, &light_dir), 0.0);
+                [
+                    (255.0 * diffuse) as u8,
+                    (102.0 * diffuse) as u8,
+                    (51.0 * diffuse) as u8,
+                    255
+                ]
+            }
+        ).with_depth_test(&mut depth_buffer, DepthFunc::Less);
+        phong_context.draw_indexed_triangles(&sphere_indices, &sphere_vertices);
+
+        // Blinn-Phong sphere (center-back): adds a specular highlight on top
+        // of the Phong diffuse term, computed from the half-vector between
+        // the view and light directions.
+        let blinn_phong_model = glm::translation(&glm::vec3(0.0, 0.0, 9.0)) *
+            glm::rotation(angle, &glm::vec3(0.0, 1.0, 0.0));
+        let blinn_phong_mvp = projection * camera.view * blinn_phong_model;
+        let blinn_phong_normal_matrix = glm::mat4_to_mat3(&blinn_phong_model);
+        let view_pos = camera_controller.position;
+        let mut blinn_phong_context = RenderContext::new(
+            CullMode::Back,
+            &mut texture_buffer,
+            |v: &mut Vertex| {
+                let p = v.position;
+                v.position = (blinn_phong_model * glm::vec4(p.x, p.y, p.z, 1.0)).xyz();
+                v.normal = blinn_phong_normal_matrix * v.normal;
+                blinn_phong_mvp * glm::vec4(p.x, p.y, p.z, 1.0)
+            },
+            |v: &Vertex| {
+                let normal = glm::normalize(&v.normal);
+                let view_dir = glm::normalize(&(view_pos - v.position));
+                let diffuse = f32::max(glm::dot(&normal, &light_dir), 0.0);
+                let specular = blinn_phong_specular(normal, view_dir, light_dir, 32.0);
+                [
+                    (60.0 * diffuse + 255.0 * specular).min(255.0) as u8,
+                    (60.0 * diffuse + 255.0 * specular).min(255.0) as u8,
+                    (200.0 * diffuse + 255.0 * specular).min(255.0) as u8,
+                    255
+                ]
+            }
+        ).with_depth_test(&mut depth_buffer, DepthFunc::Less);
+        blinn_phong_context.draw_indexed_triangles(&sphere_indices, &sphere_vertices);
+
+        let mut resolved = texture_buffer.resolve(SUPERSAMPLE);
 
         if let Some(fps) = fps_counter.update() {
-            println!("Fps: {}", fps);
+            last_fps = fps;
+        }
+        draw_text(&mut resolved, 4, 4,
+            &format!("Fps:{} ({:.2}ms avg {:.2}ms)", last_fps, fps_counter.last_frame_ms(), fps_counter.average_ms()),
+            &[255, 255, 0, 255]);
+
+        present_texture.update(None, &resolved.buffer, resolved.pitch())?;
+
+        canvas.copy(&present_texture, None, None)?;
+        canvas.present();
+
+        if let Some(recorder) = &mut gif_recorder {
+            if let Err(e) = recorder.capture(&resolved) {
+                println!("Failed to capture GIF frame: {}", e);
+            }
         }
     }
-}
\ No newline at end of file
+
+    Ok(())
+}
+
+pub fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+